@@ -0,0 +1,151 @@
+//! Encode/decode throughput of Borsh, Wincode and JSON on three payload shapes:
+//! a small struct, a large vector and a tree of nested enums.
+//!
+//! Run with `cargo bench --bench formats`. Encoded sizes are printed before each group.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use criterion::measurement::WallTime;
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+use generic_storage::{BorshSer, JsonSer, Serializer, WincodeSer};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    wincode::SchemaWrite,
+    wincode::SchemaRead,
+)]
+struct Small {
+    id: u64,
+    name: String,
+    active: bool,
+    score: f64,
+}
+
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    wincode::SchemaWrite,
+    wincode::SchemaRead,
+)]
+struct Ledger {
+    entries: Vec<Small>,
+    checksums: Vec<u64>,
+}
+
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    wincode::SchemaWrite,
+    wincode::SchemaRead,
+)]
+enum Instruction {
+    Noop,
+    Transfer { from: u32, to: u32, lamports: u64 },
+    Memo(String),
+    Batch(Vec<Instruction>),
+}
+
+fn small() -> Small {
+    Small {
+        id: 42,
+        name: "Pratham".to_string(),
+        active: true,
+        score: 97.5,
+    }
+}
+
+fn ledger(len: usize) -> Ledger {
+    Ledger {
+        entries: (0..len as u64)
+            .map(|id| Small {
+                id,
+                name: format!("account-{id}"),
+                active: id % 3 != 0,
+                score: id as f64 * 0.25,
+            })
+            .collect(),
+        checksums: (0..len as u64 * 4)
+            .map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+            .collect(),
+    }
+}
+
+/// A batch tree `depth` levels deep with `width` children per batch.
+fn instructions(depth: u32, width: u32) -> Instruction {
+    if depth == 0 {
+        return match width % 3 {
+            0 => Instruction::Noop,
+            1 => Instruction::Memo("settle".to_string()),
+            _ => Instruction::Transfer {
+                from: width,
+                to: width + 1,
+                lamports: 5_000,
+            },
+        };
+    }
+    Instruction::Batch((0..width).map(|i| instructions(depth - 1, i + 1)).collect())
+}
+
+fn bench_format<T, S: Serializer<T>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    serializer: S,
+    value: &T,
+) {
+    let format = serializer.format();
+    let bytes = serializer.to_bytes(value).unwrap();
+    let len = serializer.serialized_len(value).unwrap();
+    assert_eq!(len, bytes.len());
+    println!("  {format:<8} {len:>10} bytes");
+
+    group.throughput(Throughput::Bytes(len as u64));
+    group.bench_function(BenchmarkId::new("encode", format), |b| {
+        b.iter(|| serializer.to_bytes(black_box(value)).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("decode", format), |b| {
+        b.iter(|| serializer.from_bytes(black_box(&bytes)).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("serialized_len", format), |b| {
+        b.iter(|| serializer.serialized_len(black_box(value)).unwrap())
+    });
+}
+
+fn bench_payload<T>(c: &mut Criterion, name: &str, value: &T)
+where
+    BorshSer: Serializer<T>,
+    WincodeSer: Serializer<T>,
+    JsonSer: Serializer<T>,
+{
+    println!("{name}:");
+    let mut group = c.benchmark_group(name);
+    bench_format(&mut group, BorshSer, value);
+    bench_format(&mut group, WincodeSer, value);
+    bench_format(&mut group, JsonSer, value);
+    group.finish();
+}
+
+fn bench_formats(c: &mut Criterion) {
+    bench_payload(c, "small_struct", &small());
+    bench_payload(c, "large_vec", &ledger(10_000));
+    bench_payload(c, "nested_enum", &instructions(4, 6));
+}
+
+criterion_group!(benches, bench_formats);
+criterion_main!(benches);
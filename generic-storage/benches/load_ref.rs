@@ -0,0 +1,54 @@
+//! Owned `load` vs borrowed `load_ref` on a large account-like blob.
+//!
+//! Run with `cargo bench --bench load_ref`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use generic_storage::{Borrowable, Storage, WincodeSer};
+
+#[derive(wincode::SchemaWrite, wincode::SchemaRead)]
+struct Account {
+    owner: String,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+#[derive(wincode::SchemaRead)]
+struct AccountRef<'a> {
+    owner: &'a str,
+    lamports: u64,
+    data: &'a [u8],
+}
+
+impl Borrowable for Account {
+    type Borrowed<'a> = AccountRef<'a>;
+}
+
+fn bench_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wincode_account");
+    for size in [1 << 10, 1 << 16, 1 << 20] {
+        let account = Account {
+            owner: "Escrow1111111111111111111111111111111111111".to_string(),
+            lamports: 1_000_000,
+            data: vec![7; size],
+        };
+        let mut storage = Storage::new(WincodeSer);
+        storage.save(&account).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("load", size), &storage, |b, s| {
+            b.iter(|| {
+                let account = s.load().unwrap();
+                black_box((account.owner.len(), account.lamports, account.data.len()))
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("load_ref", size), &storage, |b, s| {
+            b.iter(|| {
+                let account = s.load_ref().unwrap();
+                black_box((account.owner.len(), account.lamports, account.data.len()))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_load);
+criterion_main!(benches);
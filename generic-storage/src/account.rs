@@ -0,0 +1,155 @@
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+
+use crate::{Backend, Format, MemoryBackend, Serializer, Storage, StorageError};
+
+/// Length of the discriminator Anchor writes in front of every account body.
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// Anchor's account discriminator: the first 8 bytes of `sha256("account:<Name>")`.
+pub fn account_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    let hash = Sha256::new()
+        .chain_update(b"account:")
+        .chain_update(name.as_bytes())
+        .finalize();
+    let mut out = [0; DISCRIMINATOR_LEN];
+    out.copy_from_slice(&hash[..DISCRIMINATOR_LEN]);
+    out
+}
+
+/// How an account's data is used: discriminator, encoded body, then unused space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountLayout {
+    pub discriminator: [u8; DISCRIMINATOR_LEN],
+    /// Bytes taken by the serialized body.
+    pub body_len: usize,
+    /// Allocated bytes after the body that hold no data.
+    pub unused: usize,
+}
+
+impl AccountLayout {
+    /// Total length of the account data.
+    pub fn data_len(&self) -> usize {
+        DISCRIMINATOR_LEN + self.body_len + self.unused
+    }
+}
+
+/// Reads and writes Anchor-style account data: an 8-byte discriminator followed by the wrapped
+/// serializer's body, zero-padded up to the account's allocated space.
+/// Use it on bytes fetched from LiteSVM (`svm.get_account(&key)?.data`) or an account snapshot.
+#[derive(Debug, Clone, Copy)]
+pub struct AnchorAccount<S> {
+    inner: S,
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    space: Option<usize>,
+}
+
+impl<S> AnchorAccount<S> {
+    pub fn new(inner: S, discriminator: [u8; DISCRIMINATOR_LEN]) -> Self {
+        AnchorAccount {
+            inner,
+            discriminator,
+            space: None,
+        }
+    }
+
+    /// Uses the discriminator Anchor derives for `#[account] pub struct <name>`.
+    pub fn for_account(inner: S, name: &str) -> Self {
+        AnchorAccount::new(inner, account_discriminator(name))
+    }
+
+    /// Pads writes with zeros to `space` bytes, discriminator included, like
+    /// `#[account(init, space = ...)]`.
+    pub fn with_space(mut self, space: usize) -> Self {
+        self.space = Some(space);
+        self
+    }
+
+    pub fn discriminator(&self) -> [u8; DISCRIMINATOR_LEN] {
+        self.discriminator
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Validates the discriminator, decodes the body and reports the unused trailing space.
+    pub fn inspect<T>(&self, data: &[u8]) -> Result<(T, AccountLayout), StorageError>
+    where
+        S: Serializer<T>,
+    {
+        if data.len() < DISCRIMINATOR_LEN {
+            return Err(StorageError::Malformed(
+                "account data shorter than its discriminator",
+            ));
+        }
+        let (head, body) = data.split_at(DISCRIMINATOR_LEN);
+        let mut found = [0; DISCRIMINATOR_LEN];
+        found.copy_from_slice(head);
+        if found != self.discriminator {
+            return Err(StorageError::DiscriminatorMismatch {
+                expected: self.discriminator,
+                found,
+            });
+        }
+        let (value, body_len) = self.inner.from_bytes_prefix(body)?;
+        let layout = AccountLayout {
+            discriminator: found,
+            body_len,
+            unused: body.len() - body_len,
+        };
+        Ok((value, layout))
+    }
+}
+
+impl<T, S> Serializer<T> for AnchorAccount<S>
+where
+    S: Serializer<T>,
+{
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        let body = self.inner.to_bytes(data)?;
+        let needed = DISCRIMINATOR_LEN + body.len();
+        let space = self.space.unwrap_or(needed);
+        if needed > space {
+            return Err(StorageError::AccountTooSmall { needed, space });
+        }
+        let mut out = Vec::with_capacity(space);
+        out.extend_from_slice(&self.discriminator);
+        out.extend_from_slice(&body);
+        out.resize(space, 0);
+        Ok(out)
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        self.inspect(bytes).map(|(value, _)| value)
+    }
+
+    fn format(&self) -> Format {
+        self.inner.format()
+    }
+
+    fn serialized_len(&self, data: &T) -> Result<usize, StorageError> {
+        let needed = DISCRIMINATOR_LEN + self.inner.serialized_len(data)?;
+        match self.space {
+            Some(space) if needed > space => Err(StorageError::AccountTooSmall { needed, space }),
+            Some(space) => Ok(space),
+            None => Ok(needed),
+        }
+    }
+}
+
+/// `Storage` over Anchor-style account data.
+pub type AccountStorage<T, S, B = MemoryBackend> = Storage<T, AnchorAccount<S>, B>;
+
+impl<T, S, B> Storage<T, AnchorAccount<S>, B>
+where
+    S: Serializer<T>,
+    B: Backend,
+{
+    /// Like `load`, but also reports how the stored account bytes are used.
+    pub fn load_with_layout(&self) -> Result<(T, AccountLayout), StorageError> {
+        let bytes = self.backend.read()?.ok_or(StorageError::Empty)?;
+        self.serializer.inspect(&bytes)
+    }
+}
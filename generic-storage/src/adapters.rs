@@ -0,0 +1,140 @@
+#[cfg(feature = "encrypt")]
+use std::fmt;
+
+#[cfg(feature = "encrypt")]
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+#[cfg(feature = "encrypt")]
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+
+use crate::{Format, Serializer, StorageError};
+
+#[cfg(feature = "compress")]
+/// LZ4-compresses whatever the wrapped serializer produces.
+/// The uncompressed length is prepended so decompression can allocate once.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Compressed<S> {
+    inner: S,
+}
+
+#[cfg(feature = "compress")]
+impl<S> Compressed<S> {
+    pub fn new(inner: S) -> Self {
+        Compressed { inner }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[cfg(feature = "compress")]
+impl<T, S> Serializer<T> for Compressed<S>
+where
+    S: Serializer<T>,
+{
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        let raw = self.inner.to_bytes(data)?;
+        Ok(lz4_flex::compress_prepend_size(&raw))
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        let raw = lz4_flex::decompress_size_prepended(bytes).map_err(StorageError::Decompress)?;
+        self.inner.from_bytes(&raw)
+    }
+
+    /// Reports the wrapped serializer's format; the bytes themselves are compressed.
+    fn format(&self) -> Format {
+        self.inner.format()
+    }
+}
+
+#[cfg(feature = "encrypt")]
+/// Length of the random nonce written in front of every ciphertext.
+pub const NONCE_LEN: usize = 12;
+
+/// Length of the Poly1305 tag appended to every ciphertext.
+#[cfg(feature = "encrypt")]
+const TAG_LEN: usize = 16;
+
+#[cfg(feature = "encrypt")]
+/// Encrypts whatever the wrapped serializer produces with ChaCha20-Poly1305.
+/// Each write uses a fresh random nonce, stored as `nonce || ciphertext || tag`.
+/// The format id is bound as associated data, so bytes cannot be replayed under another format.
+#[derive(Clone)]
+pub struct Encrypted<S> {
+    inner: S,
+    cipher: ChaCha20Poly1305,
+}
+
+#[cfg(feature = "encrypt")]
+impl<S> Encrypted<S> {
+    /// `key` is the caller's 256-bit secret; keep it out of the storage location.
+    pub fn new(inner: S, key: [u8; 32]) -> Self {
+        Encrypted {
+            inner,
+            cipher: ChaCha20Poly1305::new(&key.into()),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[cfg(feature = "encrypt")]
+impl<S: fmt::Debug> fmt::Debug for Encrypted<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encrypted")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "encrypt")]
+impl<T, S> Serializer<T> for Encrypted<S>
+where
+    S: Serializer<T>,
+{
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        let plaintext = self.inner.to_bytes(data)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: &plaintext,
+            aad: &[self.inner.format().id()],
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| StorageError::Encrypt)?;
+
+        let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        if bytes.len() < NONCE_LEN {
+            return Err(StorageError::Malformed("missing encryption nonce"));
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: &[self.inner.format().id()],
+        };
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| StorageError::Decrypt)?;
+        self.inner.from_bytes(&plaintext)
+    }
+
+    /// Reports the wrapped serializer's format; the bytes themselves are ciphertext.
+    fn format(&self) -> Format {
+        self.inner.format()
+    }
+
+    fn serialized_len(&self, data: &T) -> Result<usize, StorageError> {
+        Ok(NONCE_LEN + self.inner.serialized_len(data)? + TAG_LEN)
+    }
+}
@@ -0,0 +1,142 @@
+use std::io::ErrorKind;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{watch, RwLock};
+
+use crate::fs_backend::{temp_path, TEMP_ATTEMPTS};
+use crate::{Serializer, StorageError};
+
+/// The async counterpart of `SharedStorage` over a `FileBackend`: a cloneable handle to one
+/// file, read and written with tokio's file I/O so tasks never block the runtime on disk.
+///
+/// Loads run concurrently, saves are exclusive and atomic (temp file, `fsync`, rename), and
+/// every write bumps the generation published on `subscribe()`.
+pub struct AsyncFileStorage<T, S> {
+    inner: Arc<AsyncShared<T, S>>,
+}
+
+struct AsyncShared<T, S> {
+    path: PathBuf,
+    serializer: S,
+    lock: RwLock<()>,
+    changes: watch::Sender<u64>,
+    // Only decoded values cross the handle, so it is `Send`/`Sync` whatever `T` is.
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, S: Serializer<T>> AsyncFileStorage<T, S> {
+    pub fn new(serializer: S, path: impl Into<PathBuf>) -> Self {
+        let (changes, _) = watch::channel(0);
+        AsyncFileStorage {
+            inner: Arc::new(AsyncShared {
+                path: path.into(),
+                serializer,
+                lock: RwLock::new(()),
+                changes,
+                _marker: PhantomData,
+            }),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    pub async fn save(&self, value: &T) -> Result<(), StorageError> {
+        let bytes = self.inner.serializer.to_bytes(value)?;
+        let _guard = self.inner.lock.write().await;
+        write_atomic(&self.inner.path, &bytes).await?;
+        self.notify();
+        Ok(())
+    }
+
+    pub async fn load(&self) -> Result<T, StorageError> {
+        let _guard = self.inner.lock.read().await;
+        match fs::read(&self.inner.path).await {
+            Ok(bytes) => self.inner.serializer.from_bytes(&bytes),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(StorageError::Empty),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn has_data(&self) -> bool {
+        let _guard = self.inner.lock.read().await;
+        fs::metadata(&self.inner.path)
+            .await
+            .is_ok_and(|m| m.is_file())
+    }
+
+    pub async fn clear(&self) -> Result<(), StorageError> {
+        let _guard = self.inner.lock.write().await;
+        match fs::remove_file(&self.inner.path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        self.notify();
+        Ok(())
+    }
+
+    /// A receiver whose value is the number of writes through this handle (and its clones);
+    /// `changed().await` wakes on the next save or clear.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.inner.changes.subscribe()
+    }
+
+    fn notify(&self) {
+        self.inner
+            .changes
+            .send_modify(|generation| *generation += 1);
+    }
+}
+
+impl<T, S> Clone for AsyncFileStorage<T, S> {
+    fn clone(&self) -> Self {
+        AsyncFileStorage {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+/// Async version of `fs_backend::create_temp`.
+async fn create_temp(path: &Path) -> Result<(PathBuf, fs::File), StorageError> {
+    for _ in 0..TEMP_ATTEMPTS {
+        let tmp_path = temp_path(path)?;
+        let opened = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)
+            .await;
+        match opened {
+            Ok(file) => return Ok((tmp_path, file)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(std::io::Error::new(ErrorKind::AlreadyExists, "no free temp file name").into())
+}
+
+/// Async version of `fs_backend::write_atomic` for an already-encoded buffer.
+async fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), StorageError> {
+    let (tmp_path, mut tmp) = create_temp(path).await?;
+    let result = async {
+        tmp.write_all(bytes).await?;
+        tmp.sync_all().await?;
+        fs::rename(&tmp_path, path).await
+    }
+    .await;
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path).await;
+        return Err(e.into());
+    }
+
+    #[cfg(unix)]
+    fs::File::open(crate::fs_backend::parent_dir(path))
+        .await?
+        .sync_all()
+        .await?;
+    Ok(())
+}
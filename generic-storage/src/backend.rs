@@ -0,0 +1,56 @@
+use alloc::vec::Vec;
+
+use crate::StorageError;
+
+/// Where a `Storage` keeps its serialized bytes.
+/// `Serializer` decides *how* a value becomes bytes, a `Backend` decides *where* those bytes live.
+pub trait Backend {
+    /// Returns the stored bytes, or `None` if nothing has been written yet.
+    fn read(&self) -> Result<Option<Vec<u8>>, StorageError>;
+    fn write(&mut self, bytes: &[u8]) -> Result<(), StorageError>;
+    fn clear(&mut self) -> Result<(), StorageError>;
+    fn exists(&self) -> bool;
+}
+
+/// Keeps the bytes in memory only. This is the default backend of `Storage::new`.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryBackend {
+    pub(crate) data: Option<Vec<u8>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn read(&self) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.data.clone())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), StorageError> {
+        self.data = Some(bytes.to_vec());
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), StorageError> {
+        self.data = None;
+        Ok(())
+    }
+
+    fn exists(&self) -> bool {
+        self.data.is_some()
+    }
+}
+
+/// Backends that hold their bytes in memory and can lend them out, enabling `Storage::load_ref`.
+pub trait BorrowBackend: Backend {
+    fn bytes(&self) -> Option<&[u8]>;
+}
+
+impl BorrowBackend for MemoryBackend {
+    fn bytes(&self) -> Option<&[u8]> {
+        self.data.as_deref()
+    }
+}
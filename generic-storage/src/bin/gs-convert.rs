@@ -0,0 +1,116 @@
+//! Re-encodes a stored blob between Borsh, Wincode and JSON using a type descriptor.
+//!
+//! ```text
+//! gs-convert --from borsh --to json --schema '{ name: string, age: u32, balance: f64 }' person.bin
+//! ```
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use generic_storage::{decode_value, encode_value, Format, StorageError, TypeDesc};
+
+const USAGE: &str = "usage: gs-convert --from <format> --to <format> \
+(--schema <descriptor> | --schema-file <path>) [<input> [<output>]]
+
+Formats: borsh, wincode, json. Input and output default to stdin and stdout (`-`).
+Descriptors: bool u8..u128 i8..i128 f32 f64 string bytes vec<T> option<T> [T; N]
+(T, U) { field: T } enum { A, B(T), C { field: T } }";
+
+struct Args {
+    from: Format,
+    to: Format,
+    schema: TypeDesc,
+    input: String,
+    output: String,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let (mut from, mut to, mut schema) = (None, None, None);
+    let mut paths = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{flag} needs a value"));
+        match arg.as_str() {
+            "--from" => from = Some(format_arg(&value("--from")?)?),
+            "--to" => to = Some(format_arg(&value("--to")?)?),
+            "--schema" => schema = Some(value("--schema")?),
+            "--schema-file" => {
+                let path = value("--schema-file")?;
+                schema = Some(fs::read_to_string(&path).map_err(|e| format!("{path}: {e}"))?);
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown flag {flag}")),
+            path => paths.push(path.to_string()),
+        }
+    }
+    if paths.len() > 2 {
+        return Err("expected at most an input and an output path".into());
+    }
+    let mut paths = paths.into_iter();
+    Ok(Args {
+        from: from.ok_or("missing --from")?,
+        to: to.ok_or("missing --to")?,
+        schema: schema
+            .ok_or("missing --schema")?
+            .parse()
+            .map_err(|e: StorageError| e.to_string())?,
+        input: paths.next().unwrap_or_else(|| "-".into()),
+        output: paths.next().unwrap_or_else(|| "-".into()),
+    })
+}
+
+fn format_arg(name: &str) -> Result<Format, String> {
+    match Format::from_name(name) {
+        Some(format @ (Format::Borsh | Format::Wincode | Format::Json)) => Ok(format),
+        _ => Err(format!("unsupported format {name:?}")),
+    }
+}
+
+fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let input = if args.input == "-" {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes)?;
+        bytes
+    } else {
+        fs::read(&args.input)?
+    };
+
+    let value = decode_value(&input, args.from, &args.schema)?;
+    let output = if args.to == Format::Json {
+        // Validate against the schema, then pretty-print for humans.
+        encode_value(&value, Format::Json, &args.schema)?;
+        let mut text = serde_json::to_vec_pretty(&value)?;
+        text.push(b'\n');
+        text
+    } else {
+        encode_value(&value, args.to, &args.schema)?
+    };
+
+    if args.output == "-" {
+        io::stdout().write_all(&output)?;
+    } else {
+        fs::write(&args.output, &output)?;
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if std::env::args().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(why) => {
+            eprintln!("gs-convert: {why}");
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("gs-convert: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(feature = "json")]
+use crate::JsonSer;
+#[cfg(feature = "wincode")]
+use crate::WincodeSer;
+use crate::{open_envelope, Checksummed, Serializer, StorageError};
+
+/// Types with a borrowed view that can be decoded straight out of the stored bytes,
+/// e.g. `String` -> `&str` or an account struct -> a struct of `&[u8]`/`&str` fields.
+pub trait Borrowable {
+    type Borrowed<'a>;
+}
+
+impl Borrowable for String {
+    type Borrowed<'a> = &'a str;
+}
+
+impl Borrowable for Vec<u8> {
+    type Borrowed<'a> = &'a [u8];
+}
+
+/// Serializers that can decode `T::Borrowed<'a>` without copying out of `bytes`.
+pub trait BorrowSerializer<T: Borrowable>: Serializer<T> {
+    #[allow(clippy::wrong_self_convention)]
+    fn from_bytes_ref<'a>(&self, bytes: &'a [u8]) -> Result<T::Borrowed<'a>, StorageError>;
+}
+
+#[cfg(feature = "wincode")]
+impl<T> BorrowSerializer<T> for WincodeSer
+where
+    T: Borrowable,
+    WincodeSer: Serializer<T>,
+    for<'a> T::Borrowed<'a>:
+        wincode::SchemaRead<'a, wincode::config::DefaultConfig, Dst = T::Borrowed<'a>>,
+{
+    fn from_bytes_ref<'a>(&self, bytes: &'a [u8]) -> Result<T::Borrowed<'a>, StorageError> {
+        wincode::deserialize(bytes).map_err(StorageError::WincodeDecode)
+    }
+}
+
+#[cfg(feature = "json")]
+/// Borrowed `&str` fields only work for JSON strings without escape sequences.
+impl<T> BorrowSerializer<T> for JsonSer
+where
+    T: Borrowable,
+    JsonSer: Serializer<T>,
+    for<'a> T::Borrowed<'a>: serde::Deserialize<'a>,
+{
+    fn from_bytes_ref<'a>(&self, bytes: &'a [u8]) -> Result<T::Borrowed<'a>, StorageError> {
+        serde_json::from_slice(bytes).map_err(StorageError::JsonDecode)
+    }
+}
+
+/// The envelope is only a prefix, so the verified body can still be borrowed.
+impl<T, S> BorrowSerializer<T> for Checksummed<S>
+where
+    T: Borrowable,
+    S: BorrowSerializer<T>,
+{
+    fn from_bytes_ref<'a>(&self, bytes: &'a [u8]) -> Result<T::Borrowed<'a>, StorageError> {
+        let (header, body) = open_envelope(bytes)?;
+        let expected = self.inner().format();
+        if header.format_id != expected.id() {
+            return Err(StorageError::FormatMismatch {
+                expected,
+                found: header.format_id,
+            });
+        }
+        self.inner().from_bytes_ref(body)
+    }
+}
@@ -0,0 +1,29 @@
+use alloc::vec::Vec;
+
+use crate::{Backend, Serializer, Storage, StorageError};
+
+/// Decodes `bytes` with `from` and re-encodes the value with `to`,
+/// e.g. turning a Borsh blob into JSON for any `T` both serializers support.
+pub fn convert<T, F, To>(bytes: &[u8], from: &F, to: &To) -> Result<Vec<u8>, StorageError>
+where
+    F: Serializer<T>,
+    To: Serializer<T>,
+{
+    let value = from.from_bytes(bytes)?;
+    to.to_bytes(&value)
+}
+
+impl<T, S, B> Storage<T, S, B>
+where
+    S: Serializer<T>,
+    B: Backend,
+{
+    /// Copies the stored value into `target`, re-encoding it with `target`'s serializer.
+    pub fn convert_into<S2, B2>(&self, target: &mut Storage<T, S2, B2>) -> Result<(), StorageError>
+    where
+        S2: Serializer<T>,
+        B2: Backend,
+    {
+        target.save(&self.load()?)
+    }
+}
@@ -0,0 +1,150 @@
+use alloc::vec::Vec;
+
+#[cfg(all(feature = "borsh", feature = "wincode", feature = "json"))]
+use crate::{BorshSer, JsonSer, WincodeSer};
+use crate::{Format, Serializer, StorageError};
+
+/// Magic bytes at the start of every envelope.
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"GSTO";
+
+/// magic (4) + format id (1) + body length u32 LE (4) + CRC32 of the body u32 LE (4).
+pub const ENVELOPE_HEADER_LEN: usize = 13;
+
+/// Parsed envelope header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvelopeHeader {
+    pub format_id: u8,
+    pub len: u32,
+    pub crc32: u32,
+}
+
+/// Wraps `body` in a self-describing, checksummed envelope.
+pub fn seal_envelope(format: Format, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(ENVELOPE_HEADER_LEN + body.len());
+    out.extend_from_slice(&ENVELOPE_MAGIC);
+    out.push(format.id());
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(&crc32fast::hash(body).to_le_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+/// Checks magic, length and checksum, returning the header and the verified body.
+pub fn open_envelope(bytes: &[u8]) -> Result<(EnvelopeHeader, &[u8]), StorageError> {
+    if bytes.len() < ENVELOPE_HEADER_LEN || bytes[..4] != ENVELOPE_MAGIC {
+        return Err(StorageError::Malformed("missing envelope header"));
+    }
+    let (header, body) = bytes.split_at(ENVELOPE_HEADER_LEN);
+    let header = EnvelopeHeader {
+        format_id: header[4],
+        len: u32::from_le_bytes([header[5], header[6], header[7], header[8]]),
+        crc32: u32::from_le_bytes([header[9], header[10], header[11], header[12]]),
+    };
+    if body.len() != header.len as usize {
+        return Err(StorageError::Malformed(
+            "envelope length does not match body",
+        ));
+    }
+    let actual = crc32fast::hash(body);
+    if actual != header.crc32 {
+        return Err(StorageError::ChecksumMismatch {
+            expected: header.crc32,
+            actual,
+        });
+    }
+    Ok((header, body))
+}
+
+/// Wraps a `Serializer` so its payloads are written inside an envelope and verified on read.
+/// Corruption surfaces as `StorageError::ChecksumMismatch` instead of a confusing decode error.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Checksummed<S> {
+    inner: S,
+}
+
+impl<S> Checksummed<S> {
+    pub fn new(inner: S) -> Self {
+        Checksummed { inner }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<T, S> Serializer<T> for Checksummed<S>
+where
+    S: Serializer<T>,
+{
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        let body = self.inner.to_bytes(data)?;
+        Ok(seal_envelope(self.inner.format(), &body))
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        let (header, body) = open_envelope(bytes)?;
+        let expected = self.inner.format();
+        if header.format_id != expected.id() {
+            return Err(StorageError::FormatMismatch {
+                expected,
+                found: header.format_id,
+            });
+        }
+        self.inner.from_bytes(body)
+    }
+
+    fn format(&self) -> Format {
+        self.inner.format()
+    }
+
+    fn serialized_len(&self, data: &T) -> Result<usize, StorageError> {
+        Ok(ENVELOPE_HEADER_LEN + self.inner.serialized_len(data)?)
+    }
+}
+
+/// Reads enveloped payloads written by Borsh, Wincode or JSON, picking the decoder from
+/// the header. Writes use the format given to `new`. Other formats report `UnknownFormat`.
+#[cfg(all(feature = "borsh", feature = "wincode", feature = "json"))]
+#[derive(Debug, Clone, Copy)]
+pub struct AutoDetect {
+    write_as: Format,
+}
+
+#[cfg(all(feature = "borsh", feature = "wincode", feature = "json"))]
+impl AutoDetect {
+    pub fn new(write_as: Format) -> Self {
+        AutoDetect { write_as }
+    }
+}
+
+#[cfg(all(feature = "borsh", feature = "wincode", feature = "json"))]
+impl<T> Serializer<T> for AutoDetect
+where
+    BorshSer: Serializer<T>,
+    WincodeSer: Serializer<T>,
+    JsonSer: Serializer<T>,
+{
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        let body = match self.write_as {
+            Format::Borsh => BorshSer.to_bytes(data)?,
+            Format::Wincode => WincodeSer.to_bytes(data)?,
+            Format::Json => JsonSer.to_bytes(data)?,
+            other => return Err(StorageError::UnknownFormat(other.id())),
+        };
+        Ok(seal_envelope(self.write_as, &body))
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        let (header, body) = open_envelope(bytes)?;
+        match Format::from_id(header.format_id) {
+            Some(Format::Borsh) => BorshSer.from_bytes(body),
+            Some(Format::Wincode) => WincodeSer.from_bytes(body),
+            Some(Format::Json) => JsonSer.from_bytes(body),
+            _ => Err(StorageError::UnknownFormat(header.format_id)),
+        }
+    }
+
+    fn format(&self) -> Format {
+        self.write_as
+    }
+}
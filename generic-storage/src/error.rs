@@ -0,0 +1,225 @@
+use alloc::string::String;
+use core::fmt;
+
+use crate::Format;
+
+/// Every failure a `Serializer` or `Storage` can report.
+/// Encode/decode variants are split per backend and keep the library's own error as the source.
+#[derive(Debug)]
+pub enum StorageError {
+    /// `Storage::load` was called before anything was saved.
+    Empty,
+    #[cfg(feature = "borsh")]
+    BorshEncode(borsh::io::Error),
+    #[cfg(feature = "borsh")]
+    BorshDecode(borsh::io::Error),
+    #[cfg(feature = "wincode")]
+    WincodeEncode(wincode::error::WriteError),
+    #[cfg(feature = "wincode")]
+    WincodeDecode(wincode::error::ReadError),
+    #[cfg(feature = "json")]
+    JsonEncode(serde_json::Error),
+    #[cfg(feature = "json")]
+    JsonDecode(serde_json::Error),
+    #[cfg(feature = "bincode")]
+    BincodeEncode(bincode::Error),
+    #[cfg(feature = "bincode")]
+    BincodeDecode(bincode::Error),
+    #[cfg(feature = "msgpack")]
+    MsgPackEncode(rmp_serde::encode::Error),
+    #[cfg(feature = "msgpack")]
+    MsgPackDecode(rmp_serde::decode::Error),
+    #[cfg(feature = "cbor")]
+    CborEncode(ciborium::ser::Error<std::io::Error>),
+    #[cfg(feature = "cbor")]
+    CborDecode(ciborium::de::Error<std::io::Error>),
+    #[cfg(feature = "postcard")]
+    PostcardEncode(postcard::Error),
+    #[cfg(feature = "postcard")]
+    PostcardDecode(postcard::Error),
+    /// A `Backend` failed to read or write its bytes.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// A `DirBackend` key that cannot be used as a plain file name.
+    InvalidKey(String),
+    /// Stored bytes do not follow the container layout (e.g. a truncated `KeyedStorage` file).
+    Malformed(&'static str),
+    /// A tagged payload was written by a different serializer than the one reading it.
+    FormatMismatch { expected: Format, found: u8 },
+    /// The payload's schema version is newer than the reader's.
+    VersionTooNew { found: u32, current: u32 },
+    /// No migration is registered to upgrade a payload out of version `from`.
+    MissingMigration { from: u32 },
+    /// The envelope checksum does not match its body: the stored bytes are corrupted.
+    ChecksumMismatch { expected: u32, actual: u32 },
+    /// An envelope names a format id this build does not know or cannot decode here.
+    UnknownFormat(u8),
+    /// `Storage::rollback` asked for more snapshots than the history holds.
+    NoSnapshot { requested: usize, available: usize },
+    /// Account data starts with another account type's discriminator.
+    DiscriminatorMismatch { expected: [u8; 8], found: [u8; 8] },
+    /// The encoded account does not fit in its allocated space.
+    AccountTooSmall { needed: usize, space: usize },
+    /// A `TypeDesc` string that could not be parsed.
+    InvalidDescriptor(String),
+    /// A JSON value does not have the shape its `TypeDesc` asks for.
+    SchemaMismatch { path: String, expected: String },
+    /// `Compressed` could not decompress the stored bytes.
+    #[cfg(feature = "compress")]
+    Decompress(lz4_flex::block::DecompressError),
+    /// `Encrypted` failed to seal a payload.
+    #[cfg(feature = "encrypt")]
+    Encrypt,
+    /// `Encrypted` could not authenticate the stored bytes: wrong key or tampered data.
+    #[cfg(feature = "encrypt")]
+    Decrypt,
+}
+
+impl StorageError {
+    /// True when the error only means "nothing stored yet" rather than a real failure.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, StorageError::Empty)
+    }
+
+    /// True when the stored bytes failed their integrity check.
+    pub fn is_corruption(&self) -> bool {
+        matches!(self, StorageError::ChecksumMismatch { .. })
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Empty => write!(f, "no data stored"),
+            #[cfg(feature = "borsh")]
+            StorageError::BorshEncode(e) => write!(f, "borsh encode failed: {e}"),
+            #[cfg(feature = "borsh")]
+            StorageError::BorshDecode(e) => write!(f, "borsh decode failed: {e}"),
+            #[cfg(feature = "wincode")]
+            StorageError::WincodeEncode(e) => write!(f, "wincode encode failed: {e}"),
+            #[cfg(feature = "wincode")]
+            StorageError::WincodeDecode(e) => write!(f, "wincode decode failed: {e}"),
+            #[cfg(feature = "json")]
+            StorageError::JsonEncode(e) => write!(f, "json encode failed: {e}"),
+            #[cfg(feature = "json")]
+            StorageError::JsonDecode(e) => write!(f, "json decode failed: {e}"),
+            #[cfg(feature = "bincode")]
+            StorageError::BincodeEncode(e) => write!(f, "bincode encode failed: {e}"),
+            #[cfg(feature = "bincode")]
+            StorageError::BincodeDecode(e) => write!(f, "bincode decode failed: {e}"),
+            #[cfg(feature = "msgpack")]
+            StorageError::MsgPackEncode(e) => write!(f, "msgpack encode failed: {e}"),
+            #[cfg(feature = "msgpack")]
+            StorageError::MsgPackDecode(e) => write!(f, "msgpack decode failed: {e}"),
+            #[cfg(feature = "cbor")]
+            StorageError::CborEncode(e) => write!(f, "cbor encode failed: {e}"),
+            #[cfg(feature = "cbor")]
+            StorageError::CborDecode(e) => write!(f, "cbor decode failed: {e}"),
+            #[cfg(feature = "postcard")]
+            StorageError::PostcardEncode(e) => write!(f, "postcard encode failed: {e}"),
+            #[cfg(feature = "postcard")]
+            StorageError::PostcardDecode(e) => write!(f, "postcard decode failed: {e}"),
+            #[cfg(feature = "std")]
+            StorageError::Io(e) => write!(f, "storage i/o failed: {e}"),
+            StorageError::InvalidKey(key) => write!(f, "invalid storage key {key:?}"),
+            StorageError::Malformed(what) => write!(f, "malformed storage bytes: {what}"),
+            StorageError::FormatMismatch { expected, found } => {
+                write!(f, "expected {expected} payload, found format tag {found}")
+            }
+            StorageError::VersionTooNew { found, current } => {
+                write!(
+                    f,
+                    "schema version {found} is newer than supported version {current}"
+                )
+            }
+            StorageError::MissingMigration { from } => {
+                write!(f, "no migration registered from schema version {from}")
+            }
+            StorageError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "stored data is corrupted: checksum {actual:#010x}, expected {expected:#010x}"
+            ),
+            StorageError::UnknownFormat(id) => write!(f, "unknown format id {id}"),
+            StorageError::NoSnapshot {
+                requested,
+                available,
+            } => write!(
+                f,
+                "cannot roll back {requested} writes: only {available} snapshots kept"
+            ),
+            StorageError::DiscriminatorMismatch { expected, found } => write!(
+                f,
+                "account discriminator {found:02x?} does not match expected {expected:02x?}"
+            ),
+            StorageError::AccountTooSmall { needed, space } => {
+                write!(f, "account needs {needed} bytes but only has {space}")
+            }
+            StorageError::InvalidDescriptor(why) => write!(f, "invalid type descriptor: {why}"),
+            StorageError::SchemaMismatch { path, expected } => {
+                write!(
+                    f,
+                    "value at {path} does not match schema: expected {expected}"
+                )
+            }
+            #[cfg(feature = "compress")]
+            StorageError::Decompress(e) => write!(f, "decompression failed: {e}"),
+            #[cfg(feature = "encrypt")]
+            StorageError::Encrypt => write!(f, "encryption failed"),
+            #[cfg(feature = "encrypt")]
+            StorageError::Decrypt => write!(f, "decryption failed: wrong key or tampered data"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for StorageError {
+    fn from(e: std::io::Error) -> Self {
+        StorageError::Io(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::Empty
+            | StorageError::InvalidKey(_)
+            | StorageError::Malformed(_)
+            | StorageError::FormatMismatch { .. }
+            | StorageError::VersionTooNew { .. }
+            | StorageError::MissingMigration { .. }
+            | StorageError::ChecksumMismatch { .. }
+            | StorageError::UnknownFormat(_)
+            | StorageError::NoSnapshot { .. }
+            | StorageError::DiscriminatorMismatch { .. }
+            | StorageError::AccountTooSmall { .. }
+            | StorageError::InvalidDescriptor(_)
+            | StorageError::SchemaMismatch { .. } => None,
+            #[cfg(feature = "encrypt")]
+            StorageError::Encrypt | StorageError::Decrypt => None,
+            #[cfg(feature = "borsh")]
+            StorageError::BorshEncode(e) | StorageError::BorshDecode(e) => Some(e),
+            #[cfg(feature = "wincode")]
+            StorageError::WincodeEncode(e) => Some(e),
+            #[cfg(feature = "wincode")]
+            StorageError::WincodeDecode(e) => Some(e),
+            #[cfg(feature = "json")]
+            StorageError::JsonEncode(e) | StorageError::JsonDecode(e) => Some(e),
+            #[cfg(feature = "bincode")]
+            StorageError::BincodeEncode(e) | StorageError::BincodeDecode(e) => Some(e),
+            #[cfg(feature = "msgpack")]
+            StorageError::MsgPackEncode(e) => Some(e),
+            #[cfg(feature = "msgpack")]
+            StorageError::MsgPackDecode(e) => Some(e),
+            #[cfg(feature = "cbor")]
+            StorageError::CborEncode(e) => Some(e),
+            #[cfg(feature = "cbor")]
+            StorageError::CborDecode(e) => Some(e),
+            #[cfg(feature = "postcard")]
+            StorageError::PostcardEncode(e) | StorageError::PostcardDecode(e) => Some(e),
+            StorageError::Io(e) => Some(e),
+            #[cfg(feature = "compress")]
+            StorageError::Decompress(e) => Some(e),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! A serde `Serializer` that writes nothing and only looks for NaN and infinities, which
+//! `serde_json` would otherwise quietly write as `null`.
+
+use core::fmt;
+
+use serde::ser::{self, Serialize};
+
+/// Walks `value` and reports whether it holds a non-finite `f32` or `f64` anywhere.
+///
+/// Errors raised by `value`'s own `Serialize` impl are not ours to report: the real
+/// serializer runs next and hits them again.
+pub(crate) fn has_non_finite<T: Serialize + ?Sized>(value: &T) -> bool {
+    matches!(value.serialize(FloatCheck), Err(Found::NonFinite))
+}
+
+#[derive(Debug)]
+enum Found {
+    NonFinite,
+    Custom,
+}
+
+impl fmt::Display for Found {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Found::NonFinite => f.write_str("non-finite float"),
+            Found::Custom => f.write_str("serialize error"),
+        }
+    }
+}
+
+impl ser::StdError for Found {}
+
+impl ser::Error for Found {
+    fn custom<M: fmt::Display>(_msg: M) -> Self {
+        Found::Custom
+    }
+}
+
+struct FloatCheck;
+
+type Checked = Result<(), Found>;
+
+impl ser::Serializer for FloatCheck {
+    type Ok = ();
+    type Error = Found;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_f32(self, v: f32) -> Checked {
+        if v.is_finite() {
+            Ok(())
+        } else {
+            Err(Found::NonFinite)
+        }
+    }
+
+    fn serialize_f64(self, v: f64) -> Checked {
+        if v.is_finite() {
+            Ok(())
+        } else {
+            Err(Found::NonFinite)
+        }
+    }
+
+    fn serialize_bool(self, _: bool) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_i8(self, _: i8) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_i16(self, _: i16) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_i32(self, _: i32) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_i64(self, _: i64) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_i128(self, _: i128) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_u8(self, _: u8) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_u16(self, _: u16) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_u32(self, _: u32) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_u64(self, _: u64) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_u128(self, _: u128) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_char(self, _: char) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_str(self, _: &str) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Checked {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Checked {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Checked {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self, Found> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, Found> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, Found> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Found> {
+        Ok(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self, Found> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, Found> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Found> {
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for FloatCheck {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Checked {
+        value.serialize(FloatCheck)
+    }
+
+    fn end(self) -> Checked {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for FloatCheck {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Checked {
+        value.serialize(FloatCheck)
+    }
+
+    fn end(self) -> Checked {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for FloatCheck {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Checked {
+        value.serialize(FloatCheck)
+    }
+
+    fn end(self) -> Checked {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for FloatCheck {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Checked {
+        value.serialize(FloatCheck)
+    }
+
+    fn end(self) -> Checked {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for FloatCheck {
+    type Ok = ();
+    type Error = Found;
+
+    // serde_json already rejects non-finite float keys itself.
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, _: &T) -> Checked {
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Checked {
+        value.serialize(FloatCheck)
+    }
+
+    fn end(self) -> Checked {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for FloatCheck {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, _: &'static str, value: &T) -> Checked {
+        value.serialize(FloatCheck)
+    }
+
+    fn end(self) -> Checked {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for FloatCheck {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, _: &'static str, value: &T) -> Checked {
+        value.serialize(FloatCheck)
+    }
+
+    fn end(self) -> Checked {
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+//! Optional serde-based backends, each behind its own cargo feature.
+
+#[cfg(any(feature = "bincode", feature = "msgpack", feature = "cbor"))]
+use std::io::{Read, Write};
+
+#[cfg(any(
+    feature = "bincode",
+    feature = "msgpack",
+    feature = "cbor",
+    feature = "postcard"
+))]
+use alloc::vec::Vec;
+
+#[cfg(any(
+    feature = "bincode",
+    feature = "msgpack",
+    feature = "cbor",
+    feature = "postcard"
+))]
+use crate::{Format, Serializer, StorageError};
+
+/// Bincode 1.x with its default (little-endian, fixed-int) options.
+#[cfg(feature = "bincode")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BincodeSer;
+
+#[cfg(feature = "bincode")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Serializer<T> for BincodeSer {
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        bincode::serialize(data).map_err(StorageError::BincodeEncode)
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        bincode::deserialize(bytes).map_err(StorageError::BincodeDecode)
+    }
+
+    fn format(&self) -> Format {
+        Format::Bincode
+    }
+
+    fn serialized_len(&self, data: &T) -> Result<usize, StorageError> {
+        let len = bincode::serialized_size(data).map_err(StorageError::BincodeEncode)?;
+        Ok(len as usize)
+    }
+
+    fn serialize_into<W: Write>(&self, data: &T, writer: W) -> Result<(), StorageError> {
+        bincode::serialize_into(writer, data).map_err(StorageError::BincodeEncode)
+    }
+
+    fn deserialize_from<R: Read>(&self, reader: R) -> Result<T, StorageError> {
+        bincode::deserialize_from(reader).map_err(StorageError::BincodeDecode)
+    }
+}
+
+/// MessagePack with struct fields encoded by name, so other MessagePack clients can read them.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgPackSer;
+
+#[cfg(feature = "msgpack")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Serializer<T> for MsgPackSer {
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        rmp_serde::to_vec_named(data).map_err(StorageError::MsgPackEncode)
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        rmp_serde::from_slice(bytes).map_err(StorageError::MsgPackDecode)
+    }
+
+    fn format(&self) -> Format {
+        Format::MessagePack
+    }
+
+    fn serialize_into<W: Write>(&self, data: &T, mut writer: W) -> Result<(), StorageError> {
+        rmp_serde::encode::write_named(&mut writer, data).map_err(StorageError::MsgPackEncode)
+    }
+
+    fn deserialize_from<R: Read>(&self, reader: R) -> Result<T, StorageError> {
+        rmp_serde::from_read(reader).map_err(StorageError::MsgPackDecode)
+    }
+}
+
+/// CBOR (RFC 8949) via `ciborium`.
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborSer;
+
+#[cfg(feature = "cbor")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Serializer<T> for CborSer {
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        let mut out = Vec::new();
+        ciborium::into_writer(data, &mut out).map_err(StorageError::CborEncode)?;
+        Ok(out)
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        ciborium::from_reader(bytes).map_err(StorageError::CborDecode)
+    }
+
+    fn format(&self) -> Format {
+        Format::Cbor
+    }
+
+    fn serialize_into<W: Write>(&self, data: &T, writer: W) -> Result<(), StorageError> {
+        ciborium::into_writer(data, writer).map_err(StorageError::CborEncode)
+    }
+
+    fn deserialize_from<R: Read>(&self, reader: R) -> Result<T, StorageError> {
+        ciborium::from_reader(reader).map_err(StorageError::CborDecode)
+    }
+}
+
+/// Postcard's compact varint encoding, as used by embedded tooling.
+#[cfg(feature = "postcard")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostcardSer;
+
+#[cfg(feature = "postcard")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Serializer<T> for PostcardSer {
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        postcard::to_allocvec(data).map_err(StorageError::PostcardEncode)
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        postcard::from_bytes(bytes).map_err(StorageError::PostcardDecode)
+    }
+
+    fn format(&self) -> Format {
+        Format::Postcard
+    }
+}
@@ -0,0 +1,289 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{Backend, MemoryBackend, StorageError};
+
+/// Backends that can hand out a reader/writer so values are streamed instead of buffered.
+/// Used by `Storage::save_streamed` and `Storage::load_streamed`.
+pub trait StreamBackend: Backend {
+    /// Runs `read` against the stored bytes, or returns `None` if nothing has been written yet.
+    fn read_with<R>(
+        &self,
+        read: impl FnOnce(&mut dyn Read) -> Result<R, StorageError>,
+    ) -> Result<Option<R>, StorageError>;
+
+    /// Replaces the stored bytes with whatever `write` produces.
+    /// If `write` fails the previous contents are left in place.
+    fn write_with(
+        &mut self,
+        write: impl FnOnce(&mut dyn Write) -> Result<(), StorageError>,
+    ) -> Result<(), StorageError>;
+}
+
+impl StreamBackend for MemoryBackend {
+    fn read_with<R>(
+        &self,
+        read: impl FnOnce(&mut dyn Read) -> Result<R, StorageError>,
+    ) -> Result<Option<R>, StorageError> {
+        self.data
+            .as_deref()
+            .map(|mut bytes| read(&mut bytes))
+            .transpose()
+    }
+
+    fn write_with(
+        &mut self,
+        write: impl FnOnce(&mut dyn Write) -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        let mut buf = Vec::new();
+        write(&mut buf)?;
+        self.data = Some(buf);
+        Ok(())
+    }
+}
+
+/// Persists the bytes to a single file.
+/// Writes go to a sibling temp file which is fsynced and then renamed over the target,
+/// so a crash mid-write leaves either the old or the new contents, never a torn file.
+#[derive(Debug, Clone)]
+pub struct FileBackend {
+    path: PathBuf,
+}
+
+impl FileBackend {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileBackend { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Backend for FileBackend {
+    fn read(&self) -> Result<Option<Vec<u8>>, StorageError> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(StorageError::Io(e)),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), StorageError> {
+        write_atomic(&self.path, |file| {
+            file.write_all(bytes).map_err(StorageError::Io)
+        })
+    }
+
+    fn clear(&mut self) -> Result<(), StorageError> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(StorageError::Io(e)),
+        }
+    }
+
+    fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+}
+
+impl StreamBackend for FileBackend {
+    fn read_with<R>(
+        &self,
+        read: impl FnOnce(&mut dyn Read) -> Result<R, StorageError>,
+    ) -> Result<Option<R>, StorageError> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(StorageError::Io(e)),
+        };
+        read(&mut BufReader::new(file)).map(Some)
+    }
+
+    fn write_with(
+        &mut self,
+        write: impl FnOnce(&mut dyn Write) -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        write_atomic(&self.path, write)
+    }
+}
+
+/// Persists the bytes as `<dir>/<key>`, one file per key.
+/// Several `DirBackend`s can share a directory; use `with_key` to address a sibling entry.
+#[derive(Debug, Clone)]
+pub struct DirBackend {
+    dir: PathBuf,
+    file: FileBackend,
+}
+
+impl DirBackend {
+    /// Keys must be non-empty plain file names (no path separators, `.` or `..`).
+    pub fn new(dir: impl Into<PathBuf>, key: &str) -> Result<Self, StorageError> {
+        let dir = dir.into();
+        validate_key(key)?;
+        let file = FileBackend::new(dir.join(key));
+        Ok(DirBackend { dir, file })
+    }
+
+    pub fn with_key(&self, key: &str) -> Result<Self, StorageError> {
+        DirBackend::new(self.dir.clone(), key)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Lists the keys currently stored in the directory, sorted.
+    pub fn keys(&self) -> Result<Vec<String>, StorageError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(StorageError::Io(e)),
+        };
+        let mut keys = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(StorageError::Io)?;
+            if !entry.file_type().map_err(StorageError::Io)?.is_file() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                if !is_temp_name(name) {
+                    keys.push(name.to_string());
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+impl Backend for DirBackend {
+    fn read(&self) -> Result<Option<Vec<u8>>, StorageError> {
+        self.file.read()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), StorageError> {
+        fs::create_dir_all(&self.dir).map_err(StorageError::Io)?;
+        self.file.write(bytes)
+    }
+
+    fn clear(&mut self) -> Result<(), StorageError> {
+        self.file.clear()
+    }
+
+    fn exists(&self) -> bool {
+        self.file.exists()
+    }
+}
+
+impl StreamBackend for DirBackend {
+    fn read_with<R>(
+        &self,
+        read: impl FnOnce(&mut dyn Read) -> Result<R, StorageError>,
+    ) -> Result<Option<R>, StorageError> {
+        self.file.read_with(read)
+    }
+
+    fn write_with(
+        &mut self,
+        write: impl FnOnce(&mut dyn Write) -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        fs::create_dir_all(&self.dir).map_err(StorageError::Io)?;
+        self.file.write_with(write)
+    }
+}
+
+fn validate_key(key: &str) -> Result<(), StorageError> {
+    let bad = key.is_empty()
+        || key == "."
+        || key == ".."
+        || key.contains(['/', '\\', '\0'])
+        || is_temp_name(key);
+    if bad {
+        return Err(StorageError::InvalidKey(key.to_string()));
+    }
+    Ok(())
+}
+
+fn is_temp_name(name: &str) -> bool {
+    name.starts_with('.') && name.ends_with(".tmp")
+}
+
+/// Temp file + fsync + rename + fsync of the parent directory.
+/// `write` streams the new contents into the (buffered) temp file.
+pub(crate) fn write_atomic(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), StorageError>,
+) -> Result<(), StorageError> {
+    let (tmp_path, tmp) = create_temp(path)?;
+    let result = (|| {
+        let mut tmp = BufWriter::new(tmp);
+        write(&mut tmp)?;
+        let tmp = tmp.into_inner().map_err(|e| e.into_error())?;
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    #[cfg(unix)]
+    File::open(parent_dir(path))?.sync_all()?;
+    Ok(())
+}
+
+/// How many fresh temp names `create_temp` tries before giving up, in case stale files
+/// from a crashed process with a reused pid are in the way.
+pub(crate) const TEMP_ATTEMPTS: u32 = 16;
+
+/// Creates a temp file for a write to `path` with `create_new`, so two writers (threads or
+/// processes) never share one.
+fn create_temp(path: &Path) -> Result<(PathBuf, File), StorageError> {
+    for _ in 0..TEMP_ATTEMPTS {
+        let tmp_path = temp_path(path)?;
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)
+        {
+            Ok(file) => return Ok((tmp_path, file)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(std::io::Error::new(ErrorKind::AlreadyExists, "no free temp file name").into())
+}
+
+/// A fresh hidden `.{name}.{pid}.{n}.tmp` sibling for a write to `path`, unique per call
+/// within this process.
+pub(crate) fn temp_path(path: &Path) -> Result<PathBuf, StorageError> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let file_name = path.file_name().ok_or_else(|| {
+        StorageError::Io(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "path has no file name",
+        ))
+    })?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    Ok(path.with_file_name(tmp_name))
+}
+
+/// The directory whose entry must be synced for a rename of `path` to be durable.
+#[cfg(unix)]
+pub(crate) fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    }
+}
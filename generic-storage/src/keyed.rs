@@ -0,0 +1,178 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::{Backend, MemoryBackend, Serializer, StorageError};
+
+/// A small key/value store built on the same `Serializer`s as `Storage`.
+/// `S` encodes both the keys `K` and the values `T`; entries are kept ordered by `K`.
+/// Every mutation writes the whole map through to the `Backend`.
+pub struct KeyedStorage<K, T, S, B = MemoryBackend>
+where
+    K: Ord,
+    S: Serializer<K> + Serializer<T>,
+    B: Backend,
+{
+    entries: BTreeMap<K, Vec<u8>>,
+    backend: B,
+    serializer: S,
+    _marker: PhantomData<T>,
+}
+
+impl<K, T, S> KeyedStorage<K, T, S>
+where
+    K: Ord,
+    S: Serializer<K> + Serializer<T>,
+{
+    pub fn new(serializer: S) -> Self {
+        KeyedStorage {
+            entries: BTreeMap::new(),
+            backend: MemoryBackend::new(),
+            serializer,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, T, S, B> KeyedStorage<K, T, S, B>
+where
+    K: Ord,
+    S: Serializer<K> + Serializer<T>,
+    B: Backend,
+{
+    /// Opens the store on `backend`, loading whatever entries it already holds.
+    pub fn open(serializer: S, backend: B) -> Result<Self, StorageError> {
+        let mut entries = BTreeMap::new();
+        if let Some(bytes) = backend.read()? {
+            for (key, value) in decode_entries(&bytes)? {
+                let key: K = serializer.from_bytes(key)?;
+                entries.insert(key, value.to_vec());
+            }
+        }
+        Ok(KeyedStorage {
+            entries,
+            backend,
+            serializer,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Inserts or replaces the value stored under `key`.
+    /// If the backend write fails the previous value is kept.
+    pub fn insert(&mut self, key: K, value: &T) -> Result<(), StorageError>
+    where
+        K: Clone,
+    {
+        let bytes = Serializer::<T>::to_bytes(&self.serializer, value)?;
+        let previous = self.entries.insert(key.clone(), bytes);
+        if let Err(e) = self.flush() {
+            match previous {
+                Some(old) => self.entries.insert(key, old),
+                None => self.entries.remove(&key),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<T>, StorageError> {
+        self.entries
+            .get(key)
+            .map(|bytes| Serializer::<T>::from_bytes(&self.serializer, bytes))
+            .transpose()
+    }
+
+    /// Removes `key` and returns the value it held, if any.
+    pub fn remove(&mut self, key: &K) -> Result<Option<T>, StorageError> {
+        let Some((key, bytes)) = self.entries.remove_entry(key) else {
+            return Ok(None);
+        };
+        if let Err(e) = self.flush() {
+            self.entries.insert(key, bytes);
+            return Err(e);
+        }
+        Serializer::<T>::from_bytes(&self.serializer, &bytes).map(Some)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Keys in ascending order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.keys()
+    }
+
+    /// Entries in ascending key order; each value is decoded lazily.
+    pub fn iter(&self) -> impl Iterator<Item = Result<(&K, T), StorageError>> + '_ {
+        self.entries.iter().map(|(key, bytes)| {
+            Serializer::<T>::from_bytes(&self.serializer, bytes).map(|value| (key, value))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    fn flush(&mut self) -> Result<(), StorageError> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for (key, value) in &self.entries {
+            let key = Serializer::<K>::to_bytes(&self.serializer, key)?;
+            put_chunk(&mut out, &key);
+            put_chunk(&mut out, value);
+        }
+        self.backend.write(&out)
+    }
+}
+
+// On-disk layout: u32 entry count, then per entry a u32-length-prefixed key and value (little endian).
+
+type RawEntry<'a> = (&'a [u8], &'a [u8]);
+
+fn put_chunk(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+fn take_u32(bytes: &mut &[u8]) -> Result<u32, StorageError> {
+    let (head, rest) = bytes
+        .split_first_chunk::<4>()
+        .ok_or(StorageError::Malformed("truncated keyed storage"))?;
+    *bytes = rest;
+    Ok(u32::from_le_bytes(*head))
+}
+
+fn take_chunk<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], StorageError> {
+    let len = take_u32(bytes)? as usize;
+    if bytes.len() < len {
+        return Err(StorageError::Malformed("truncated keyed storage"));
+    }
+    let (chunk, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(chunk)
+}
+
+fn decode_entries(mut bytes: &[u8]) -> Result<Vec<RawEntry<'_>>, StorageError> {
+    let count = take_u32(&mut bytes)?;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let key = take_chunk(&mut bytes)?;
+        let value = take_chunk(&mut bytes)?;
+        entries.push((key, value));
+    }
+    if !bytes.is_empty() {
+        return Err(StorageError::Malformed(
+            "trailing bytes after keyed storage",
+        ));
+    }
+    Ok(entries)
+}
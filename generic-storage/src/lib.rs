@@ -1,20 +1,200 @@
-use std::marker::PhantomData;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+
+mod account;
+#[cfg(any(feature = "compress", feature = "encrypt"))]
+mod adapters;
+#[cfg(feature = "async")]
+mod async_storage;
+mod backend;
+mod borrowed;
+mod convert;
+mod envelope;
+mod error;
+#[cfg(feature = "json")]
+mod finite;
+mod formats;
+#[cfg(feature = "std")]
+mod fs_backend;
+mod keyed;
+#[cfg(feature = "json")]
+mod schema;
+#[cfg(feature = "shared")]
+mod shared;
+#[cfg(all(feature = "std", any(test, feature = "proptest")))]
+pub mod testing;
+mod versioned;
+
+pub use account::{
+    account_discriminator, AccountLayout, AccountStorage, AnchorAccount, DISCRIMINATOR_LEN,
+};
+#[cfg(feature = "compress")]
+pub use adapters::Compressed;
+#[cfg(feature = "encrypt")]
+pub use adapters::{Encrypted, NONCE_LEN};
+#[cfg(feature = "async")]
+pub use async_storage::AsyncFileStorage;
+pub use backend::{Backend, BorrowBackend, MemoryBackend};
+pub use borrowed::{BorrowSerializer, Borrowable};
+pub use convert::convert;
+#[cfg(all(feature = "borsh", feature = "wincode", feature = "json"))]
+pub use envelope::AutoDetect;
+pub use envelope::{
+    open_envelope, seal_envelope, Checksummed, EnvelopeHeader, ENVELOPE_HEADER_LEN, ENVELOPE_MAGIC,
+};
+pub use error::StorageError;
+#[cfg(feature = "bincode")]
+pub use formats::BincodeSer;
+#[cfg(feature = "cbor")]
+pub use formats::CborSer;
+#[cfg(feature = "msgpack")]
+pub use formats::MsgPackSer;
+#[cfg(feature = "postcard")]
+pub use formats::PostcardSer;
+#[cfg(feature = "std")]
+pub use fs_backend::{DirBackend, FileBackend, StreamBackend};
+pub use keyed::KeyedStorage;
+#[cfg(feature = "json")]
+pub use schema::{decode_value, encode_value, TypeDesc};
+#[cfg(feature = "shared")]
+pub use shared::SharedStorage;
+pub use versioned::{read_version_header, Versioned, VERSION_HEADER_LEN};
 
 /// The core Serializer Trait that defines the structure for all implementations.
 pub trait Serializer<T> {
-    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, String>;
-    fn from_bytes(&self, bytes: &[u8]) -> Result<T, String>;
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError>;
+    #[allow(clippy::wrong_self_convention)]
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError>;
+    /// Decodes one value from the front of `bytes` and returns it with the number of bytes it
+    /// used, leaving the rest alone (e.g. the zero padding of a fixed-size account).
+    /// The default requires the value to fill `bytes`.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_bytes_prefix(&self, bytes: &[u8]) -> Result<(T, usize), StorageError> {
+        Ok((self.from_bytes(bytes)?, bytes.len()))
+    }
+    /// Which wire format this serializer produces; used to tag stored payloads.
+    fn format(&self) -> Format;
+
+    /// Number of bytes `to_bytes(data)` produces. The default encodes and measures;
+    /// serializers that can size a value without building the buffer override it.
+    fn serialized_len(&self, data: &T) -> Result<usize, StorageError> {
+        Ok(self.to_bytes(data)?.len())
+    }
+
+    /// Streams the encoding of `data` into `writer`.
+    /// The default buffers through `to_bytes`; backends that can encode incrementally override it.
+    #[cfg(feature = "std")]
+    fn serialize_into<W: Write>(&self, data: &T, mut writer: W) -> Result<(), StorageError> {
+        writer.write_all(&self.to_bytes(data)?)?;
+        Ok(())
+    }
+
+    /// Decodes the value `reader` holds. Bytes left after it are rejected or ignored as the
+    /// format's `from_bytes` does: Borsh and JSON reject them, wincode ignores them.
+    /// The default buffers through `from_bytes`.
+    #[cfg(feature = "std")]
+    fn deserialize_from<R: Read>(&self, mut reader: R) -> Result<T, StorageError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        self.from_bytes(&bytes)
+    }
+}
+
+/// Identifies a wire format. The numeric `id` is what gets written into payload headers,
+/// so existing values must never be renumbered. Every format has an id even when its
+/// cargo feature is disabled, so headers stay readable across builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Borsh,
+    Wincode,
+    Json,
+    Bincode,
+    MessagePack,
+    Cbor,
+    Postcard,
+}
+
+impl Format {
+    pub fn id(self) -> u8 {
+        match self {
+            Format::Borsh => 1,
+            Format::Wincode => 2,
+            Format::Json => 3,
+            Format::Bincode => 4,
+            Format::MessagePack => 5,
+            Format::Cbor => 6,
+            Format::Postcard => 7,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Format> {
+        match id {
+            1 => Some(Format::Borsh),
+            2 => Some(Format::Wincode),
+            3 => Some(Format::Json),
+            4 => Some(Format::Bincode),
+            5 => Some(Format::MessagePack),
+            6 => Some(Format::Cbor),
+            7 => Some(Format::Postcard),
+            _ => None,
+        }
+    }
+
+    /// Parses the lowercase name printed by `Display`, e.g. `"borsh"` or `"msgpack"`.
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "borsh" => Some(Format::Borsh),
+            "wincode" => Some(Format::Wincode),
+            "json" => Some(Format::Json),
+            "bincode" => Some(Format::Bincode),
+            "msgpack" => Some(Format::MessagePack),
+            "cbor" => Some(Format::Cbor),
+            "postcard" => Some(Format::Postcard),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Format::Borsh => "borsh",
+            Format::Wincode => "wincode",
+            Format::Json => "json",
+            Format::Bincode => "bincode",
+            Format::MessagePack => "msgpack",
+            Format::Cbor => "cbor",
+            Format::Postcard => "postcard",
+        };
+        f.write_str(name)
+    }
 }
 
+/// What a backend held before a write; `None` when nothing was stored.
+type Snapshot = Option<Vec<u8>>;
+
 /// The generic Storage container.
 /// `T` is the type being stored.
 /// `S` is the Serializer implementation.
-pub struct Storage<T, S>
+/// `B` is the Backend the bytes are persisted to (in-memory unless chosen otherwise).
+pub struct Storage<T, S, B = MemoryBackend>
 where
     S: Serializer<T>,
+    B: Backend,
 {
-    data: Option<Vec<u8>>,
+    backend: B,
     serializer: S,
+    /// Bytes replaced by each recent write, oldest first.
+    history: VecDeque<Snapshot>,
+    history_limit: usize,
     _marker: PhantomData<T>,
 }
 
@@ -23,25 +203,164 @@ where
     S: Serializer<T>,
 {
     pub fn new(serializer: S) -> Self {
+        Storage::with_backend(serializer, MemoryBackend::new())
+    }
+}
+
+impl<T, S, B> Storage<T, S, B>
+where
+    S: Serializer<T>,
+    B: Backend,
+{
+    pub fn with_backend(serializer: S, backend: B) -> Self {
         Storage {
-            data: None,
+            backend,
             serializer,
+            history: VecDeque::new(),
+            history_limit: 0,
             _marker: PhantomData,
         }
     }
 
-    pub fn save(&mut self, value: &T) -> Result<(), String> {
-        self.data = Some(self.serializer.to_bytes(value)?);
+    /// Keeps snapshots of the bytes replaced by the last `limit` saves, updates and clears,
+    /// so they can be restored with `rollback`. History is off (`0`) by default.
+    pub fn with_history(mut self, limit: usize) -> Self {
+        self.history_limit = limit;
+        while self.history.len() > limit {
+            self.history.pop_front();
+        }
+        self
+    }
+
+    pub fn save(&mut self, value: &T) -> Result<(), StorageError> {
+        let bytes = self.serializer.to_bytes(value)?;
+        let previous = self.snapshot()?;
+        self.backend.write(&bytes)?;
+        self.record(previous);
         Ok(())
     }
 
-    pub fn load(&self) -> Result<T, String> {
-        let bytes = self.data.as_ref().ok_or("No data stored")?;
-        self.serializer.from_bytes(bytes)
+    pub fn load(&self) -> Result<T, StorageError> {
+        let bytes = self.backend.read()?.ok_or(StorageError::Empty)?;
+        self.serializer.from_bytes(&bytes)
     }
 
     pub fn has_data(&self) -> bool {
-        self.data.is_some()
+        self.backend.exists()
+    }
+
+    /// Removes whatever is stored; `load` returns `StorageError::Empty` afterwards.
+    pub fn clear(&mut self) -> Result<(), StorageError> {
+        let previous = self.snapshot()?;
+        self.backend.clear()?;
+        self.record(previous);
+        Ok(())
+    }
+
+    /// Loads the value, lets `f` mutate it and saves the result as one step.
+    /// If `f` returns an error, or the new value fails to encode or write, the stored bytes
+    /// are left as they were and the error is returned.
+    pub fn update<R, E>(&mut self, f: impl FnOnce(&mut T) -> Result<R, E>) -> Result<R, E>
+    where
+        E: From<StorageError>,
+    {
+        let previous = self.backend.read()?.ok_or(StorageError::Empty)?;
+        let mut value = self.serializer.from_bytes(&previous)?;
+        let out = f(&mut value)?;
+        let bytes = self.serializer.to_bytes(&value)?;
+        if let Err(e) = self.backend.write(&bytes) {
+            // File backends write atomically; this restores backends that do not.
+            let _ = self.backend.write(&previous);
+            return Err(e.into());
+        }
+        self.record(Some(Some(previous)));
+        Ok(out)
+    }
+
+    /// Restores the value from before the `n`th most recent recorded write (`rollback(1)`
+    /// undoes the last one) and drops the snapshots newer than it.
+    pub fn rollback(&mut self, n: usize) -> Result<(), StorageError> {
+        let available = self.history.len();
+        if n > available {
+            return Err(StorageError::NoSnapshot {
+                requested: n,
+                available,
+            });
+        }
+        if n == 0 {
+            return Ok(());
+        }
+        match &self.history[available - n] {
+            Some(bytes) => self.backend.write(bytes)?,
+            None => self.backend.clear()?,
+        }
+        self.history.truncate(available - n);
+        Ok(())
+    }
+
+    /// Number of snapshots `rollback` can currently go back.
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// The currently stored bytes, read only when history is on.
+    fn snapshot(&self) -> Result<Option<Snapshot>, StorageError> {
+        if self.history_limit == 0 {
+            return Ok(None);
+        }
+        self.backend.read().map(Some)
+    }
+
+    fn record(&mut self, previous: Option<Snapshot>) {
+        let Some(previous) = previous else { return };
+        if self.history_limit == 0 {
+            return;
+        }
+        if self.history.len() == self.history_limit {
+            self.history.pop_front();
+        }
+        self.history.push_back(previous);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S, B> Storage<T, S, B>
+where
+    S: Serializer<T>,
+    B: StreamBackend,
+{
+    /// Like `save`, but encodes straight into the backend without building the whole byte buffer.
+    pub fn save_streamed(&mut self, value: &T) -> Result<(), StorageError> {
+        let previous = self.snapshot()?;
+        let serializer = &self.serializer;
+        self.backend
+            .write_with(|writer| serializer.serialize_into(value, writer))?;
+        self.record(previous);
+        Ok(())
+    }
+
+    /// Like `load`, but decodes straight from the backend's reader.
+    pub fn load_streamed(&self) -> Result<T, StorageError> {
+        self.backend
+            .read_with(|reader| self.serializer.deserialize_from(reader))?
+            .ok_or(StorageError::Empty)
+    }
+}
+
+impl<T, S, B> Storage<T, S, B>
+where
+    T: Borrowable,
+    S: BorrowSerializer<T>,
+    B: BorrowBackend,
+{
+    /// Decodes a view that borrows from the stored bytes instead of allocating owned fields.
+    pub fn load_ref(&self) -> Result<T::Borrowed<'_>, StorageError> {
+        let bytes = self.backend.bytes().ok_or(StorageError::Empty)?;
+        self.serializer.from_bytes_ref(bytes)
     }
 }
 
@@ -49,51 +368,178 @@ where
 // 2. Implement Three Serializers
 // ======================================
 
+#[cfg(feature = "borsh")]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct BorshSer;
+#[cfg(feature = "borsh")]
 impl<T: borsh::BorshSerialize + borsh::BorshDeserialize> Serializer<T> for BorshSer {
-    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, String> {
-        borsh::to_vec(data).map_err(|e| e.to_string())
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        borsh::to_vec(data).map_err(StorageError::BorshEncode)
     }
 
-    fn from_bytes(&self, bytes: &[u8]) -> Result<T, String> {
-        borsh::from_slice(bytes).map_err(|e| e.to_string())
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        borsh::from_slice(bytes).map_err(StorageError::BorshDecode)
+    }
+
+    fn format(&self) -> Format {
+        Format::Borsh
+    }
+
+    fn serialized_len(&self, data: &T) -> Result<usize, StorageError> {
+        borsh::object_length(data).map_err(StorageError::BorshEncode)
+    }
+
+    fn from_bytes_prefix(&self, bytes: &[u8]) -> Result<(T, usize), StorageError> {
+        let mut rest = bytes;
+        let value = T::deserialize(&mut rest).map_err(StorageError::BorshDecode)?;
+        Ok((value, bytes.len() - rest.len()))
+    }
+
+    #[cfg(feature = "std")]
+    fn serialize_into<W: Write>(&self, data: &T, mut writer: W) -> Result<(), StorageError> {
+        data.serialize(&mut writer)
+            .map_err(StorageError::BorshEncode)
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from<R: Read>(&self, mut reader: R) -> Result<T, StorageError> {
+        borsh::from_reader(&mut reader).map_err(StorageError::BorshDecode)
     }
 }
 
+#[cfg(feature = "wincode")]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct WincodeSer;
+#[cfg(feature = "wincode")]
 impl<T> Serializer<T> for WincodeSer
 where
     T: wincode::SchemaWrite<wincode::config::DefaultConfig, Src = T>
         + for<'a> wincode::SchemaRead<'a, wincode::config::DefaultConfig, Dst = T>,
 {
-    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, String> {
-        wincode::serialize(data).map_err(|e| e.to_string())
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        wincode::serialize(data).map_err(StorageError::WincodeEncode)
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        wincode::deserialize(bytes).map_err(StorageError::WincodeDecode)
+    }
+
+    fn format(&self) -> Format {
+        Format::Wincode
+    }
+
+    fn serialized_len(&self, data: &T) -> Result<usize, StorageError> {
+        let len = wincode::serialized_size(data).map_err(StorageError::WincodeEncode)?;
+        Ok(len as usize)
     }
 
-    fn from_bytes(&self, bytes: &[u8]) -> Result<T, String> {
-        wincode::deserialize(bytes).map_err(|e| e.to_string())
+    /// Wincode reads from the front of the slice, so the value's own size is what it used.
+    fn from_bytes_prefix(&self, bytes: &[u8]) -> Result<(T, usize), StorageError> {
+        let value = self.from_bytes(bytes)?;
+        let len = self.serialized_len(&value)?;
+        Ok((value, len))
     }
+
+    // `serialize_into`/`deserialize_from` keep the buffered defaults: wincode sizes its
+    // output up front and decodes from one contiguous slice, ignoring anything after the value.
 }
 
+/// JSON via `serde_json`. NaN and infinities are refused when encoding, since JSON would
+/// store them as `null` and lose the value.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct JsonSer;
+
+#[cfg(feature = "json")]
+impl JsonSer {
+    fn check_finite<T: serde::Serialize>(data: &T) -> Result<(), StorageError> {
+        if finite::has_non_finite(data) {
+            return Err(StorageError::JsonEncode(serde::ser::Error::custom(
+                "NaN and infinite floats have no JSON representation",
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "json")]
 impl<T: serde::Serialize + serde::de::DeserializeOwned> Serializer<T> for JsonSer {
-    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, String> {
-        serde_json::to_vec(data).map_err(|e| e.to_string())
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        Self::check_finite(data)?;
+        serde_json::to_vec(data).map_err(StorageError::JsonEncode)
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        serde_json::from_slice(bytes).map_err(StorageError::JsonDecode)
     }
 
-    fn from_bytes(&self, bytes: &[u8]) -> Result<T, String> {
-        serde_json::from_slice(bytes).map_err(|e| e.to_string())
+    fn format(&self) -> Format {
+        Format::Json
+    }
+
+    fn from_bytes_prefix(&self, bytes: &[u8]) -> Result<(T, usize), StorageError> {
+        let mut values = serde_json::Deserializer::from_slice(bytes).into_iter::<T>();
+        match values.next() {
+            Some(value) => Ok((
+                value.map_err(StorageError::JsonDecode)?,
+                values.byte_offset(),
+            )),
+            None => Ok((self.from_bytes(bytes)?, bytes.len())),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn serialized_len(&self, data: &T) -> Result<usize, StorageError> {
+        Self::check_finite(data)?;
+        let mut counter = ByteCounter(0);
+        serde_json::to_writer(&mut counter, data).map_err(StorageError::JsonEncode)?;
+        Ok(counter.0)
+    }
+
+    #[cfg(feature = "std")]
+    fn serialize_into<W: Write>(&self, data: &T, writer: W) -> Result<(), StorageError> {
+        Self::check_finite(data)?;
+        serde_json::to_writer(writer, data).map_err(StorageError::JsonEncode)
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from<R: Read>(&self, reader: R) -> Result<T, StorageError> {
+        serde_json::from_reader(reader).map_err(StorageError::JsonDecode)
+    }
+}
+
+/// A writer that only counts what passes through it.
+#[cfg(all(feature = "std", feature = "json"))]
+struct ByteCounter(usize);
+
+#[cfg(all(feature = "std", feature = "json"))]
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
 
 // ======================================
 // 5. Test Data Type & 6. Write Tests
 // ======================================
-#[cfg(test)]
+#[cfg(all(
+    test,
+    feature = "std",
+    feature = "borsh",
+    feature = "wincode",
+    feature = "json"
+))]
 mod tests {
     use super::*;
     use borsh::{BorshDeserialize, BorshSerialize};
+    use proptest::prelude::*;
     use serde::{Deserialize, Serialize};
+    use testing::{f64_edge_cases, Harness};
 
     #[derive(Debug, PartialEq, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, wincode::SchemaWrite, wincode::SchemaRead)]
     struct Person {
@@ -156,4 +602,823 @@ mod tests {
         let loaded = storage.load().unwrap();
         assert_eq!(loaded, p);
     }
+
+    #[test]
+    fn test_error_variants() {
+        let storage: Storage<Person, BorshSer> = Storage::new(BorshSer);
+        assert!(storage.load().unwrap_err().is_empty());
+
+        let err = Serializer::<Person>::from_bytes(&BorshSer, &[1, 2, 3]).unwrap_err();
+        assert!(matches!(err, StorageError::BorshDecode(_)));
+        assert!(std::error::Error::source(&err).is_some());
+
+        let err = Serializer::<Person>::from_bytes(&JsonSer, b"{").unwrap_err();
+        assert!(matches!(err, StorageError::JsonDecode(_)));
+    }
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("generic-storage-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_file_backend_persists() {
+        let dir = scratch_dir("file");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("person.bin");
+        let p = Person {
+            name: "Bob".to_string(),
+            age: 51,
+            balance: 12.5,
+        };
+
+        let mut storage = Storage::with_backend(BorshSer, FileBackend::new(&path));
+        assert!(!storage.has_data());
+        assert!(storage.load().unwrap_err().is_empty());
+        storage.save(&p).unwrap();
+
+        // A fresh handle on the same path sees the value, as after a restart.
+        let reopened: Storage<Person, _, _> =
+            Storage::with_backend(BorshSer, FileBackend::new(&path));
+        assert_eq!(reopened.load().unwrap(), p);
+        assert_eq!(
+            std::fs::read_dir(&dir).unwrap().count(),
+            1,
+            "temp file left behind"
+        );
+
+        storage.clear().unwrap();
+        assert!(!reopened.has_data());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_backend_concurrent_writers() {
+        let dir = scratch_dir("concurrent");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("person.bin");
+
+        // Unsynchronised handles on one path: each write needs its own temp file.
+        std::thread::scope(|scope| {
+            for age in 0..8 {
+                let path = &path;
+                scope.spawn(move || {
+                    let mut storage = Storage::with_backend(BorshSer, FileBackend::new(path));
+                    for round in 0..20 {
+                        let p = Person {
+                            name: format!("writer-{age}-{round}"),
+                            age,
+                            balance: 0.0,
+                        };
+                        storage.save(&p).unwrap();
+                    }
+                });
+            }
+        });
+        let storage: Storage<Person, _, _> =
+            Storage::with_backend(BorshSer, FileBackend::new(&path));
+        assert!(storage.load().unwrap().name.ends_with("-19"));
+        assert_eq!(
+            std::fs::read_dir(&dir).unwrap().count(),
+            1,
+            "temp file left behind"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dir_backend_keys() {
+        let dir = scratch_dir("dir");
+        let alice = DirBackend::new(&dir, "alice").unwrap();
+        let bob = alice.with_key("bob").unwrap();
+
+        let mut a = Storage::with_backend(JsonSer, alice);
+        let mut b = Storage::with_backend(JsonSer, bob);
+        a.save(&Person {
+            name: "Alice".into(),
+            age: 45,
+            balance: 0.05,
+        })
+        .unwrap();
+        b.save(&Person {
+            name: "Bob".into(),
+            age: 51,
+            balance: 1.0,
+        })
+        .unwrap();
+
+        assert_eq!(a.backend().keys().unwrap(), vec!["alice", "bob"]);
+        assert_eq!(b.load().unwrap().name, "Bob");
+        assert!(matches!(
+            DirBackend::new(&dir, "../x"),
+            Err(StorageError::InvalidKey(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_keyed_storage() {
+        let mut people: KeyedStorage<String, Person, _> = KeyedStorage::new(BorshSer);
+        let carol = Person {
+            name: "Carol".to_string(),
+            age: 33,
+            balance: 7.25,
+        };
+        let dave = Person {
+            name: "Dave".to_string(),
+            age: 60,
+            balance: 99.0,
+        };
+
+        people.insert("dave".to_string(), &dave).unwrap();
+        people.insert("carol".to_string(), &carol).unwrap();
+        assert!(people.contains_key(&"carol".to_string()));
+        assert_eq!(people.get(&"dave".to_string()).unwrap(), Some(dave.clone()));
+        assert_eq!(people.keys().collect::<Vec<_>>(), vec!["carol", "dave"]);
+
+        let names: Vec<String> = people.iter().map(|e| e.unwrap().1.name).collect();
+        assert_eq!(names, vec!["Carol", "Dave"]);
+
+        assert_eq!(people.remove(&"carol".to_string()).unwrap(), Some(carol));
+        assert_eq!(people.remove(&"carol".to_string()).unwrap(), None);
+        assert_eq!(people.len(), 1);
+    }
+
+    #[test]
+    fn test_keyed_storage_reopens_from_file() {
+        let dir = scratch_dir("keyed");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ledger.bin");
+
+        let mut ledger = KeyedStorage::open(WincodeSer, FileBackend::new(&path)).unwrap();
+        for id in [3u64, 1, 2] {
+            ledger.insert(id, &format!("entry {id}")).unwrap();
+        }
+
+        let reopened: KeyedStorage<u64, String, _, _> =
+            KeyedStorage::open(WincodeSer, FileBackend::new(&path)).unwrap();
+        assert_eq!(reopened.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(reopened.get(&2).unwrap().as_deref(), Some("entry 2"));
+
+        std::fs::write(&path, [1, 0, 0, 0, 9]).unwrap();
+        assert!(matches!(
+            KeyedStorage::<u64, String, _, _>::open(WincodeSer, FileBackend::new(&path)),
+            Err(StorageError::Malformed(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[derive(Debug, PartialEq, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, wincode::SchemaWrite, wincode::SchemaRead)]
+    struct PersonV1 {
+        pub name: String,
+        pub age: u32,
+    }
+
+    fn check_migration<S>(serializer: S)
+    where
+        S: Serializer<PersonV1> + Serializer<Person> + Clone + 'static,
+    {
+        let mut old = Storage::new(Versioned::new(serializer.clone(), 1));
+        old.save(&PersonV1 {
+            name: "Erin".to_string(),
+            age: 28,
+        })
+        .unwrap();
+        let v1_bytes = old.backend().read().unwrap().unwrap();
+
+        let current: Versioned<Person, S> =
+            Versioned::new(serializer.clone(), 2).migrate(1, |p: PersonV1| Person {
+                name: p.name,
+                age: p.age,
+                balance: 0.0,
+            });
+        let mut backend = MemoryBackend::new();
+        backend.write(&v1_bytes).unwrap();
+        let upgraded = Storage::with_backend(current, backend);
+        assert_eq!(
+            upgraded.load().unwrap(),
+            Person {
+                name: "Erin".to_string(),
+                age: 28,
+                balance: 0.0,
+            }
+        );
+
+        // Without the migration the reader refuses instead of mis-decoding.
+        let strict: Versioned<Person, S> = Versioned::new(serializer, 2);
+        assert!(matches!(
+            strict.from_bytes(&v1_bytes),
+            Err(StorageError::MissingMigration { from: 1 })
+        ));
+
+        let v3 = [&[v1_bytes[0]][..], &3u32.to_le_bytes()].concat();
+        assert!(matches!(
+            strict.from_bytes(&v3),
+            Err(StorageError::VersionTooNew {
+                found: 3,
+                current: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_versioned_migrations() {
+        check_migration(BorshSer);
+        check_migration(WincodeSer);
+        check_migration(JsonSer);
+
+        let borsh_bytes = Versioned::new(BorshSer, 1).to_bytes(&PersonV1 {
+            name: "Frank".to_string(),
+            age: 70,
+        });
+        let json_reader: Versioned<PersonV1, _> = Versioned::new(JsonSer, 1);
+        assert!(matches!(
+            json_reader.from_bytes(&borsh_bytes.unwrap()),
+            Err(StorageError::FormatMismatch {
+                expected: Format::Json,
+                found: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_checksummed_detects_corruption() {
+        let p = Person {
+            name: "Grace".to_string(),
+            age: 39,
+            balance: 1234.5,
+        };
+        let mut storage = Storage::new(Checksummed::new(BorshSer));
+        storage.save(&p).unwrap();
+        assert_eq!(storage.load().unwrap(), p);
+
+        let mut bytes = storage.backend().read().unwrap().unwrap();
+        assert_eq!(bytes[..4], ENVELOPE_MAGIC);
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        let err =
+            Serializer::<Person>::from_bytes(&Checksummed::new(BorshSer), &bytes).unwrap_err();
+        assert!(err.is_corruption());
+
+        bytes.truncate(last);
+        assert!(matches!(
+            Serializer::<Person>::from_bytes(&Checksummed::new(BorshSer), &bytes),
+            Err(StorageError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_auto_detect_picks_serializer() {
+        let p = Person {
+            name: "Heidi".to_string(),
+            age: 24,
+            balance: -3.5,
+        };
+        let reader = AutoDetect::new(Format::Borsh);
+        for format in [Format::Borsh, Format::Wincode, Format::Json] {
+            let bytes = AutoDetect::new(format).to_bytes(&p).unwrap();
+            assert_eq!(bytes[4], format.id());
+            let loaded: Person = reader.from_bytes(&bytes).unwrap();
+            assert_eq!(loaded, p);
+        }
+
+        // Envelopes written by `Checksummed` are readable too.
+        let bytes = Checksummed::new(JsonSer).to_bytes(&p).unwrap();
+        let loaded: Person = reader.from_bytes(&bytes).unwrap();
+        assert_eq!(loaded, p);
+
+        let mut unknown = bytes.clone();
+        unknown[4] = 200;
+        assert!(matches!(
+            Serializer::<Person>::from_bytes(&reader, &unknown),
+            Err(StorageError::UnknownFormat(200))
+        ));
+    }
+
+    #[cfg(all(feature = "compress", feature = "encrypt"))]
+    #[test]
+    fn test_compressed_and_encrypted_stack() {
+        let p = Person {
+            name: "Ivan".repeat(64),
+            age: 47,
+            balance: 31.0,
+        };
+        let key = [7u8; 32];
+
+        let mut storage = Storage::new(Encrypted::new(Compressed::new(BorshSer), key));
+        storage.save(&p).unwrap();
+        assert_eq!(storage.load().unwrap(), p);
+
+        let stored = storage.backend().read().unwrap().unwrap();
+        assert!(stored.len() < borsh::to_vec(&p).unwrap().len());
+        assert!(!stored.windows(4).any(|w| w == b"Ivan"));
+
+        let wrong_key = Encrypted::new(Compressed::new(BorshSer), [8u8; 32]);
+        assert!(matches!(
+            Serializer::<Person>::from_bytes(&wrong_key, &stored),
+            Err(StorageError::Decrypt)
+        ));
+
+        let compressed = Compressed::new(JsonSer);
+        let err = Serializer::<Person>::from_bytes(&compressed, &[0xff; 8]).unwrap_err();
+        assert!(matches!(err, StorageError::Decompress(_)));
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, wincode::SchemaRead)]
+    struct PersonRef<'a> {
+        pub name: &'a str,
+        pub age: u32,
+        pub balance: f64,
+    }
+
+    impl Borrowable for Person {
+        type Borrowed<'a> = PersonRef<'a>;
+    }
+
+    fn check_load_ref<S: BorrowSerializer<Person>>(serializer: S) {
+        let p = Person {
+            name: "Judy".to_string(),
+            age: 36,
+            balance: 88.8,
+        };
+        let mut storage = Storage::new(serializer);
+        assert!(storage.load_ref().unwrap_err().is_empty());
+        storage.save(&p).unwrap();
+
+        let view = storage.load_ref().unwrap();
+        assert_eq!(view.name, "Judy");
+        assert_eq!((view.age, view.balance), (36, 88.8));
+
+        // The borrowed name points into the stored buffer rather than a fresh allocation.
+        let stored = storage.backend().bytes().unwrap().as_ptr_range();
+        assert!(stored.contains(&view.name.as_ptr()));
+    }
+
+    #[test]
+    fn test_load_ref_borrows_stored_bytes() {
+        check_load_ref(WincodeSer);
+        check_load_ref(JsonSer);
+        check_load_ref(Checksummed::new(WincodeSer));
+
+        let mut storage = Storage::new(WincodeSer);
+        storage.save(&"zero copy".to_string()).unwrap();
+        assert_eq!(storage.load_ref().unwrap(), "zero copy");
+    }
+
+    /// `rejects_trailing` is false for formats that, like wincode, stop reading once the value
+    /// is complete and ignore whatever follows.
+    fn check_streamed<S: Serializer<Person>>(
+        serializer: S,
+        path: &std::path::Path,
+        rejects_trailing: bool,
+    ) {
+        let p = Person {
+            name: "Ken".repeat(100_000),
+            age: 58,
+            balance: 0.125,
+        };
+        let mut storage = Storage::with_backend(serializer, FileBackend::new(path));
+        assert!(storage.load_streamed().unwrap_err().is_empty());
+        storage.save_streamed(&p).unwrap();
+        assert_eq!(storage.load_streamed().unwrap(), p);
+        // Streamed and buffered paths produce the same bytes.
+        assert_eq!(storage.load().unwrap(), p);
+
+        let mut trailing = std::fs::read(path).unwrap();
+        trailing.extend_from_slice(b"xx");
+        std::fs::write(path, trailing).unwrap();
+        assert_eq!(storage.load_streamed().is_err(), rejects_trailing);
+    }
+
+    #[test]
+    fn test_streamed_save_and_load() {
+        let dir = scratch_dir("streamed");
+        std::fs::create_dir_all(&dir).unwrap();
+        check_streamed(BorshSer, &dir.join("borsh.bin"), true);
+        check_streamed(WincodeSer, &dir.join("wincode.bin"), false);
+        check_streamed(JsonSer, &dir.join("person.json"), true);
+        check_streamed(Checksummed::new(BorshSer), &dir.join("enveloped.bin"), true);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn people(balance: impl Strategy<Value = f64>) -> impl Strategy<Value = Person> {
+        (any::<String>(), any::<u32>(), balance).prop_map(|(name, age, balance)| Person {
+            name,
+            age,
+            balance,
+        })
+    }
+
+    #[test]
+    fn test_serializer_properties() {
+        Harness::new(BorshSer).check(people(f64_edge_cases()));
+        Harness::new(WincodeSer)
+            .allow_trailing_bytes()
+            .check(people(f64_edge_cases()));
+        Harness::new(JsonSer).check(people(f64_edge_cases()));
+        Harness::new(Checksummed::new(BorshSer)).check(people(f64_edge_cases()));
+    }
+
+    #[test]
+    fn test_float_edge_cases() {
+        let mut p = Person {
+            name: "Zero".to_string(),
+            age: 0,
+            balance: -0.0,
+        };
+        let borsh: Person = BorshSer
+            .from_bytes(&BorshSer.to_bytes(&p).unwrap())
+            .unwrap();
+        let wincode: Person = WincodeSer
+            .from_bytes(&WincodeSer.to_bytes(&p).unwrap())
+            .unwrap();
+        let json: Person = JsonSer.from_bytes(&JsonSer.to_bytes(&p).unwrap()).unwrap();
+        for q in [borsh, wincode, json] {
+            assert!(q.balance == 0.0 && q.balance.is_sign_negative());
+        }
+
+        // Borsh refuses NaN outright; JSON has no way to write NaN or infinities at all.
+        p.balance = f64::NAN;
+        assert!(BorshSer.to_bytes(&p).is_err());
+        for balance in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            p.balance = balance;
+            assert!(matches!(
+                JsonSer.to_bytes(&p),
+                Err(StorageError::JsonEncode(_))
+            ));
+            assert!(JsonSer.serialize_into(&p, Vec::new()).is_err());
+        }
+        // Nested floats are checked too, and a `None` still writes `null`.
+        let balances = vec![Some(1.5), None, Some(f64::NAN)];
+        assert!(JsonSer.to_bytes(&balances).is_err());
+        assert_eq!(
+            JsonSer.to_bytes(&balances[..2].to_vec()).unwrap(),
+            b"[1.5,null]"
+        );
+    }
+
+    #[test]
+    fn test_serialized_len_matches_encoding() {
+        let p = Person {
+            name: "Sizing".to_string(),
+            age: 64,
+            balance: 1e-3,
+        };
+        fn check<S: Serializer<Person>>(serializer: S, p: &Person) {
+            let len = serializer.to_bytes(p).unwrap().len();
+            assert_eq!(serializer.serialized_len(p).unwrap(), len);
+        }
+        check(BorshSer, &p);
+        check(WincodeSer, &p);
+        check(JsonSer, &p);
+        check(Checksummed::new(JsonSer), &p);
+        check(Versioned::new(WincodeSer, 3), &p);
+        #[cfg(feature = "encrypt")]
+        check(Encrypted::new(BorshSer, [9; 32]), &p);
+        assert_eq!(
+            Serializer::<Person>::serialized_len(&BorshSer, &p).unwrap(),
+            4 + "Sizing".len() + 4 + 8
+        );
+    }
+
+    #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+    struct Escrow {
+        seed: u64,
+        maker: [u8; 32],
+        mint_a: [u8; 32],
+        mint_b: [u8; 32],
+        receive: u64,
+        bump: u8,
+    }
+
+    #[test]
+    fn test_anchor_account_layout() {
+        assert_eq!(
+            account_discriminator("Escrow"),
+            [31, 213, 123, 187, 186, 22, 218, 155]
+        );
+        let escrow = Escrow {
+            seed: 7,
+            maker: [1; 32],
+            mint_a: [2; 32],
+            mint_b: [3; 32],
+            receive: 1_000_000,
+            bump: 254,
+        };
+        let body_len = 8 + 32 * 3 + 8 + 1;
+
+        // Account data as LiteSVM returns it: discriminator, borsh body, zeroed spare space.
+        let mut data = account_discriminator("Escrow").to_vec();
+        data.extend(borsh::to_vec(&escrow).unwrap());
+        data.resize(200, 0);
+        let mut backend = MemoryBackend::new();
+        backend.write(&data).unwrap();
+        let storage: AccountStorage<Escrow, _> =
+            Storage::with_backend(AnchorAccount::for_account(BorshSer, "Escrow"), backend);
+        let (loaded, layout) = storage.load_with_layout().unwrap();
+        assert_eq!(loaded, escrow);
+        assert_eq!(layout.body_len, body_len);
+        assert_eq!(layout.unused, 200 - 8 - body_len);
+        assert_eq!(layout.data_len(), 200);
+
+        let vault = AnchorAccount::for_account(BorshSer, "Vault");
+        assert!(matches!(
+            Serializer::<Escrow>::from_bytes(&vault, &data),
+            Err(StorageError::DiscriminatorMismatch { .. })
+        ));
+        assert!(Serializer::<Escrow>::from_bytes(&vault, &data[..4]).is_err());
+
+        // Writes pad to the allocated space and refuse bodies that do not fit.
+        let account = AnchorAccount::for_account(BorshSer, "Escrow").with_space(200);
+        assert_eq!(account.to_bytes(&escrow).unwrap(), data);
+        assert_eq!(account.serialized_len(&escrow).unwrap(), 200);
+        let cramped = AnchorAccount::for_account(BorshSer, "Escrow").with_space(64);
+        assert!(matches!(
+            cramped.to_bytes(&escrow),
+            Err(StorageError::AccountTooSmall { needed, space: 64 }) if needed == 8 + body_len
+        ));
+
+        // Any serializer can be the body, including JSON with padding after it.
+        let json = AnchorAccount::new(JsonSer, [9; 8]).with_space(400);
+        let (decoded, layout): (Escrow, _) =
+            json.inspect(&json.to_bytes(&escrow).unwrap()).unwrap();
+        assert_eq!(decoded, escrow);
+        assert_eq!(layout.data_len(), 400);
+        assert!(layout.unused > 0);
+    }
+
+    #[test]
+    fn test_update_and_rollback() {
+        let mut storage = Storage::new(BorshSer).with_history(2);
+        let update = |s: &mut Storage<Person, BorshSer>| {
+            s.update(|p: &mut Person| {
+                p.age += 1;
+                Ok::<_, StorageError>(p.age)
+            })
+        };
+        assert!(update(&mut storage).unwrap_err().is_empty());
+
+        let p = Person {
+            name: "Txn".to_string(),
+            age: 1,
+            balance: 5.0,
+        };
+        storage.save(&p).unwrap();
+        assert_eq!(update(&mut storage).unwrap(), 2);
+
+        // A failing closure or an unencodable result leaves the stored value alone.
+        let rejected = storage.update(|p| {
+            p.age = 99;
+            Err::<(), _>(StorageError::Malformed("rejected"))
+        });
+        assert!(matches!(rejected, Err(StorageError::Malformed("rejected"))));
+        let unencodable = storage.update(|p| {
+            p.balance = f64::NAN;
+            Ok::<_, StorageError>(())
+        });
+        assert!(matches!(unencodable, Err(StorageError::BorshEncode(_))));
+        assert_eq!(storage.load().unwrap().age, 2);
+
+        storage
+            .save(&Person {
+                age: 3,
+                ..p.clone()
+            })
+            .unwrap();
+        // Limit 2: the snapshot of "nothing stored" has been dropped.
+        assert_eq!(storage.history_len(), 2);
+        storage.rollback(1).unwrap();
+        assert_eq!(storage.load().unwrap().age, 2);
+        storage.rollback(1).unwrap();
+        assert_eq!(storage.load().unwrap(), p);
+        assert!(matches!(
+            storage.rollback(1),
+            Err(StorageError::NoSnapshot {
+                requested: 1,
+                available: 0
+            })
+        ));
+
+        storage.clear().unwrap();
+        storage.rollback(1).unwrap();
+        assert_eq!(storage.load().unwrap(), p);
+    }
+
+    #[cfg(feature = "shared")]
+    #[test]
+    fn test_shared_storage_across_threads() {
+        let shared = SharedStorage::new(Storage::new(BorshSer));
+        let changes = shared.subscribe();
+        assert!(!shared.has_data());
+
+        std::thread::scope(|scope| {
+            for age in 0..4 {
+                let handle = shared.clone();
+                scope.spawn(move || {
+                    let p = Person {
+                        name: format!("writer-{age}"),
+                        age,
+                        balance: 0.0,
+                    };
+                    handle.save(&p).unwrap();
+                    // Whatever was written last, readers never see a torn value.
+                    assert!(handle.load().unwrap().name.starts_with("writer-"));
+                });
+            }
+        });
+        assert!(changes.has_changed().unwrap());
+        assert_eq!(*changes.borrow(), 4);
+        assert!(shared.with_storage(|s| s.has_data()));
+
+        shared.clear().unwrap();
+        assert!(shared.load().unwrap_err().is_empty());
+        assert_eq!(*changes.borrow(), 5);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_file_storage() {
+        let dir = scratch_dir("async");
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = AsyncFileStorage::new(JsonSer, dir.join("config.json"));
+        assert!(config.load().await.unwrap_err().is_empty());
+
+        let mut changes = config.subscribe();
+        let watcher = config.clone();
+        let waiter = tokio::spawn(async move {
+            changes.changed().await.unwrap();
+            watcher.load().await.unwrap()
+        });
+        let p = Person {
+            name: "Config".to_string(),
+            age: 3,
+            balance: 10.0,
+        };
+        config.save(&p).await.unwrap();
+        assert_eq!(waiter.await.unwrap(), p);
+        assert!(config.has_data().await);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        config.clear().await.unwrap();
+        assert!(!config.has_data().await);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_between_serializers() {
+        let p = Person {
+            name: "Ravi".to_string(),
+            age: 27,
+            balance: 12.25,
+        };
+        let borsh_bytes = BorshSer.to_bytes(&p).unwrap();
+        let json_bytes = convert::<Person, _, _>(&borsh_bytes, &BorshSer, &JsonSer).unwrap();
+        assert_eq!(json_bytes, JsonSer.to_bytes(&p).unwrap());
+        assert!(convert::<Person, _, _>(&json_bytes, &BorshSer, &JsonSer).is_err());
+
+        let mut source = Storage::new(BorshSer);
+        source.save(&p).unwrap();
+        let mut target = Storage::new(WincodeSer);
+        source.convert_into(&mut target).unwrap();
+        assert_eq!(target.load().unwrap(), p);
+    }
+
+    #[test]
+    fn test_schema_dump_matches_typed_serializers() {
+        let p = Person {
+            name: "Mira".to_string(),
+            age: 45,
+            balance: -3.5,
+        };
+        let desc: TypeDesc = "{ name: string, age: u32, balance: f64 }".parse().unwrap();
+        let borsh_bytes = BorshSer.to_bytes(&p).unwrap();
+
+        let value = decode_value(&borsh_bytes, Format::Borsh, &desc).unwrap();
+        assert_eq!(value, serde_json::to_value(&p).unwrap());
+        assert_eq!(
+            encode_value(&value, Format::Borsh, &desc).unwrap(),
+            borsh_bytes
+        );
+
+        let json_bytes = encode_value(&value, Format::Json, &desc).unwrap();
+        assert_eq!(
+            Serializer::<Person>::from_bytes(&JsonSer, &json_bytes).unwrap(),
+            p
+        );
+
+        let mut trailing = borsh_bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            decode_value(&trailing, Format::Borsh, &desc),
+            Err(StorageError::Malformed(_))
+        ));
+        let wrong = serde_json::json!({ "name": "Mira", "age": "old", "balance": 1.0 });
+        match encode_value(&wrong, Format::Borsh, &desc) {
+            Err(StorageError::SchemaMismatch { path, expected }) => {
+                assert_eq!(path, "$.age");
+                assert_eq!(expected, "u32");
+            }
+            other => panic!("expected a schema mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_schema_wire_layouts() {
+        let desc: TypeDesc =
+            "enum { Closed, Open { owner: string, amounts: vec<u16>, memo: option<u8> } }"
+                .parse()
+                .unwrap();
+        assert_eq!(desc.to_string().parse::<TypeDesc>().unwrap(), desc);
+        let value = serde_json::json!({ "Open": { "owner": "ab", "amounts": [7], "memo": null } });
+
+        // Borsh: u8 tag, u32 lengths.
+        let borsh_bytes = encode_value(&value, Format::Borsh, &desc).unwrap();
+        assert_eq!(
+            borsh_bytes,
+            [1, 2, 0, 0, 0, b'a', b'b', 1, 0, 0, 0, 7, 0, 0]
+        );
+        // Wincode: u32 tag, u64 lengths.
+        let wincode_bytes = encode_value(&value, Format::Wincode, &desc).unwrap();
+        assert_eq!(
+            wincode_bytes,
+            [1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b', 1, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0]
+        );
+        assert_eq!(
+            decode_value(&wincode_bytes, Format::Wincode, &desc).unwrap(),
+            value
+        );
+        assert_eq!(
+            decode_value(&[0, 0, 0, 0], Format::Wincode, &desc).unwrap(),
+            serde_json::json!("Closed")
+        );
+        assert!(decode_value(&[5], Format::Borsh, &desc).is_err());
+
+        assert!(matches!(
+            "{ name: strin }".parse::<TypeDesc>(),
+            Err(StorageError::InvalidDescriptor(_))
+        ));
+        assert!("vec<u8".parse::<TypeDesc>().is_err());
+        assert_eq!("bytes".parse::<TypeDesc>().unwrap().to_string(), "vec<u8>");
+        assert_eq!(
+            "[u8; 32]".parse::<TypeDesc>().unwrap().to_string(),
+            "[u8; 32]"
+        );
+    }
+
+    #[cfg(any(
+        feature = "bincode",
+        feature = "msgpack",
+        feature = "cbor",
+        feature = "postcard"
+    ))]
+    fn check_extra_format<S: Serializer<Person> + Clone>(serializer: S, format: Format) {
+        let p = Person {
+            name: "Lena".to_string(),
+            age: 31,
+            balance: -0.5,
+        };
+        assert_eq!(serializer.format(), format);
+
+        let mut storage = Storage::new(serializer.clone());
+        storage.save(&p).unwrap();
+        assert_eq!(storage.load().unwrap(), p);
+
+        let mut streamed = Vec::new();
+        serializer.serialize_into(&p, &mut streamed).unwrap();
+        assert_eq!(serializer.deserialize_from(&streamed[..]).unwrap(), p);
+
+        let enveloped = Checksummed::new(serializer.clone()).to_bytes(&p).unwrap();
+        assert_eq!(enveloped[4], format.id());
+        assert!(serializer.from_bytes(&[0xc1, 0xff]).is_err());
+
+        Harness::new(serializer)
+            .allow_trailing_bytes()
+            .check(people(f64_edge_cases()));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode1_serialization() {
+        check_extra_format(BincodeSer, Format::Bincode);
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_serialization() {
+        check_extra_format(MsgPackSer, Format::MessagePack);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_cbor_serialization() {
+        check_extra_format(CborSer, Format::Cbor);
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn test_postcard_serialization() {
+        check_extra_format(PostcardSer, Format::Postcard);
+    }
 }
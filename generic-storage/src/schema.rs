@@ -0,0 +1,605 @@
+//! Schema-less translation between Borsh/Wincode bytes and JSON, driven by a `TypeDesc`
+//! instead of a Rust type. Useful for inspecting blobs whose type is not compiled in.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use serde_json::{Map, Number, Value};
+
+use crate::{Format, StorageError};
+
+/// The shape of a value on the wire. Parse one from text with `str::parse`:
+///
+/// ```text
+/// bool  u8 u16 u32 u64 u128  i8 i16 i32 i64 i128  f32 f64  string  bytes
+/// vec<T>   option<T>   [T; N]   (T, U)   { field: T, ... }
+/// enum { Unit, Newtype(T), Tuple(T, U), Struct { field: T } }
+/// ```
+///
+/// JSON follows serde's defaults: structs are objects, tuples and arrays are arrays, unit
+/// variants are strings and other variants are `{ "Variant": payload }`. 128-bit integers
+/// outside the 64-bit range are written as decimal strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeDesc {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    F32,
+    F64,
+    String,
+    Vec(Box<TypeDesc>),
+    Option(Box<TypeDesc>),
+    Array(Box<TypeDesc>, usize),
+    Tuple(Vec<TypeDesc>),
+    Struct(Vec<(String, TypeDesc)>),
+    /// Variants in declaration order; the discriminant is the index.
+    Enum(Vec<(String, Option<TypeDesc>)>),
+}
+
+/// Decodes `bytes` written in `format` into a JSON value shaped by `desc`.
+/// JSON input is parsed and checked against `desc`.
+pub fn decode_value(bytes: &[u8], format: Format, desc: &TypeDesc) -> Result<Value, StorageError> {
+    if format == Format::Json {
+        let value = serde_json::from_slice(bytes).map_err(StorageError::JsonDecode)?;
+        encode(&value, desc, "$", &mut Vec::new(), BORSH)?;
+        return Ok(value);
+    }
+    let wire = Wire::of(format)?;
+    let mut reader = Reader { bytes };
+    let value = decode(desc, &mut reader, wire)?;
+    if !reader.bytes.is_empty() {
+        return Err(StorageError::Malformed("trailing bytes after value"));
+    }
+    Ok(value)
+}
+
+/// Encodes a JSON value shaped by `desc` into `format`.
+pub fn encode_value(
+    value: &Value,
+    format: Format,
+    desc: &TypeDesc,
+) -> Result<Vec<u8>, StorageError> {
+    if format == Format::Json {
+        encode(value, desc, "$", &mut Vec::new(), BORSH)?;
+        return serde_json::to_vec(value).map_err(StorageError::JsonEncode);
+    }
+    let mut out = Vec::new();
+    encode(value, desc, "$", &mut out, Wire::of(format)?)?;
+    Ok(out)
+}
+
+/// The parts of the layout where Borsh and Wincode differ.
+#[derive(Clone, Copy)]
+struct Wire {
+    /// Sequence lengths: Borsh uses `u32`, Wincode `u64`.
+    wide_len: bool,
+    /// Enum discriminants: Borsh uses `u8`, Wincode `u32`.
+    wide_tag: bool,
+}
+
+const BORSH: Wire = Wire {
+    wide_len: false,
+    wide_tag: false,
+};
+
+const WINCODE: Wire = Wire {
+    wide_len: true,
+    wide_tag: true,
+};
+
+impl Wire {
+    fn of(format: Format) -> Result<Wire, StorageError> {
+        match format {
+            Format::Borsh => Ok(BORSH),
+            Format::Wincode => Ok(WINCODE),
+            other => Err(StorageError::UnknownFormat(other.id())),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], StorageError> {
+        if self.bytes.len() < n {
+            return Err(StorageError::Malformed("truncated value"));
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn fixed<const N: usize>(&mut self) -> Result<[u8; N], StorageError> {
+        let mut out = [0; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn len(&mut self, wire: Wire) -> Result<usize, StorageError> {
+        let len = if wire.wide_len {
+            u64::from_le_bytes(self.fixed()?)
+        } else {
+            u64::from(u32::from_le_bytes(self.fixed()?))
+        };
+        usize::try_from(len).map_err(|_| StorageError::Malformed("length overflows usize"))
+    }
+
+    fn tag(&mut self, wire: Wire) -> Result<usize, StorageError> {
+        let tag = if wire.wide_tag {
+            u32::from_le_bytes(self.fixed()?)
+        } else {
+            u32::from(self.fixed::<1>()?[0])
+        };
+        Ok(tag as usize)
+    }
+}
+
+fn decode(desc: &TypeDesc, r: &mut Reader<'_>, wire: Wire) -> Result<Value, StorageError> {
+    Ok(match desc {
+        TypeDesc::Bool => match r.fixed::<1>()?[0] {
+            0 => Value::Bool(false),
+            1 => Value::Bool(true),
+            _ => return Err(StorageError::Malformed("invalid bool")),
+        },
+        TypeDesc::U8 => Value::from(r.fixed::<1>()?[0]),
+        TypeDesc::U16 => Value::from(u16::from_le_bytes(r.fixed()?)),
+        TypeDesc::U32 => Value::from(u32::from_le_bytes(r.fixed()?)),
+        TypeDesc::U64 => Value::from(u64::from_le_bytes(r.fixed()?)),
+        TypeDesc::U128 => {
+            let n = u128::from_le_bytes(r.fixed()?);
+            u64::try_from(n).map_or_else(|_| Value::String(n.to_string()), Value::from)
+        }
+        TypeDesc::I8 => Value::from(i8::from_le_bytes(r.fixed()?)),
+        TypeDesc::I16 => Value::from(i16::from_le_bytes(r.fixed()?)),
+        TypeDesc::I32 => Value::from(i32::from_le_bytes(r.fixed()?)),
+        TypeDesc::I64 => Value::from(i64::from_le_bytes(r.fixed()?)),
+        TypeDesc::I128 => {
+            let n = i128::from_le_bytes(r.fixed()?);
+            i64::try_from(n).map_or_else(|_| Value::String(n.to_string()), Value::from)
+        }
+        // JSON has no NaN or infinity; like serde_json, those become `null`.
+        TypeDesc::F32 => float(f64::from(f32::from_le_bytes(r.fixed()?))),
+        TypeDesc::F64 => float(f64::from_le_bytes(r.fixed()?)),
+        TypeDesc::String => {
+            let len = r.len(wire)?;
+            let text = core::str::from_utf8(r.take(len)?)
+                .map_err(|_| StorageError::Malformed("invalid utf-8 in string"))?;
+            Value::String(text.into())
+        }
+        TypeDesc::Vec(item) => {
+            let len = r.len(wire)?;
+            // The length is untrusted, so grow as items actually decode.
+            let mut items = Vec::new();
+            for _ in 0..len {
+                items.push(decode(item, r, wire)?);
+            }
+            Value::Array(items)
+        }
+        TypeDesc::Option(inner) => match r.fixed::<1>()?[0] {
+            0 => Value::Null,
+            1 => decode(inner, r, wire)?,
+            _ => return Err(StorageError::Malformed("invalid option tag")),
+        },
+        TypeDesc::Array(item, len) => Value::Array(
+            (0..*len)
+                .map(|_| decode(item, r, wire))
+                .collect::<Result<_, _>>()?,
+        ),
+        TypeDesc::Tuple(items) if items.is_empty() => Value::Null,
+        TypeDesc::Tuple(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| decode(item, r, wire))
+                .collect::<Result<_, _>>()?,
+        ),
+        TypeDesc::Struct(fields) => {
+            let mut map = Map::new();
+            for (name, field) in fields {
+                map.insert(name.clone(), decode(field, r, wire)?);
+            }
+            Value::Object(map)
+        }
+        TypeDesc::Enum(variants) => {
+            let (name, payload) = variants
+                .get(r.tag(wire)?)
+                .ok_or(StorageError::Malformed("unknown enum variant"))?;
+            match payload {
+                None => Value::String(name.clone()),
+                Some(payload) => {
+                    let mut map = Map::new();
+                    map.insert(name.clone(), decode(payload, r, wire)?);
+                    Value::Object(map)
+                }
+            }
+        }
+    })
+}
+
+fn float(x: f64) -> Value {
+    Number::from_f64(x).map_or(Value::Null, Value::Number)
+}
+
+fn encode(
+    value: &Value,
+    desc: &TypeDesc,
+    path: &str,
+    out: &mut Vec<u8>,
+    wire: Wire,
+) -> Result<(), StorageError> {
+    let mismatch = || StorageError::SchemaMismatch {
+        path: path.to_string(),
+        expected: desc.to_string(),
+    };
+    match desc {
+        TypeDesc::Bool => out.push(value.as_bool().ok_or_else(mismatch)? as u8),
+        TypeDesc::U8 => out.push(unsigned(value).ok_or_else(mismatch)?),
+        TypeDesc::U16 => out.extend(unsigned::<u16>(value).ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::U32 => out.extend(unsigned::<u32>(value).ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::U64 => out.extend(unsigned::<u64>(value).ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::U128 => out.extend(wide::<u128>(value).ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::I8 => out.extend(signed::<i8>(value).ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::I16 => out.extend(signed::<i16>(value).ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::I32 => out.extend(signed::<i32>(value).ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::I64 => out.extend(signed::<i64>(value).ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::I128 => out.extend(wide::<i128>(value).ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::F32 => out.extend((value.as_f64().ok_or_else(mismatch)? as f32).to_le_bytes()),
+        TypeDesc::F64 => out.extend(value.as_f64().ok_or_else(mismatch)?.to_le_bytes()),
+        TypeDesc::String => {
+            let text = value.as_str().ok_or_else(mismatch)?;
+            put_len(out, text.len(), wire).ok_or_else(mismatch)?;
+            out.extend_from_slice(text.as_bytes());
+        }
+        TypeDesc::Vec(item) => {
+            let items = value.as_array().ok_or_else(mismatch)?;
+            put_len(out, items.len(), wire).ok_or_else(mismatch)?;
+            for (i, v) in items.iter().enumerate() {
+                encode(v, item, &format!("{path}[{i}]"), out, wire)?;
+            }
+        }
+        TypeDesc::Option(inner) => {
+            if value.is_null() {
+                out.push(0);
+            } else {
+                out.push(1);
+                encode(value, inner, path, out, wire)?;
+            }
+        }
+        TypeDesc::Array(item, len) => {
+            let items = value
+                .as_array()
+                .filter(|a| a.len() == *len)
+                .ok_or_else(mismatch)?;
+            for (i, v) in items.iter().enumerate() {
+                encode(v, item, &format!("{path}[{i}]"), out, wire)?;
+            }
+        }
+        TypeDesc::Tuple(items) if items.is_empty() => {
+            if !value.is_null() {
+                return Err(mismatch());
+            }
+        }
+        TypeDesc::Tuple(items) => {
+            let values = value
+                .as_array()
+                .filter(|a| a.len() == items.len())
+                .ok_or_else(mismatch)?;
+            for (i, (v, item)) in values.iter().zip(items).enumerate() {
+                encode(v, item, &format!("{path}[{i}]"), out, wire)?;
+            }
+        }
+        TypeDesc::Struct(fields) => {
+            let map = value.as_object().ok_or_else(mismatch)?;
+            for (name, field) in fields {
+                let path = format!("{path}.{name}");
+                let v = map.get(name).ok_or_else(|| StorageError::SchemaMismatch {
+                    path: path.clone(),
+                    expected: field.to_string(),
+                })?;
+                encode(v, field, &path, out, wire)?;
+            }
+        }
+        TypeDesc::Enum(variants) => {
+            let (name, payload) = match value {
+                Value::String(name) => (name, None),
+                Value::Object(map) if map.len() == 1 => {
+                    let (name, payload) = map.iter().next().ok_or_else(mismatch)?;
+                    (name, Some(payload))
+                }
+                _ => return Err(mismatch()),
+            };
+            let index = variants
+                .iter()
+                .position(|(n, p)| n == name && p.is_some() == payload.is_some())
+                .ok_or_else(mismatch)?;
+            if wire.wide_tag {
+                out.extend((index as u32).to_le_bytes());
+            } else {
+                out.push(u8::try_from(index).map_err(|_| mismatch())?);
+            }
+            if let (Some(desc), Some(payload)) = (&variants[index].1, payload) {
+                encode(payload, desc, &format!("{path}.{name}"), out, wire)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn unsigned<N: TryFrom<u64>>(value: &Value) -> Option<N> {
+    N::try_from(value.as_u64()?).ok()
+}
+
+fn signed<N: TryFrom<i64>>(value: &Value) -> Option<N> {
+    N::try_from(value.as_i64()?).ok()
+}
+
+/// 128-bit integers accept JSON numbers or the decimal strings `decode_value` writes.
+fn wide<N: FromStr>(value: &Value) -> Option<N> {
+    match value {
+        Value::String(text) => text.parse().ok(),
+        Value::Number(n) => n.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+fn put_len(out: &mut Vec<u8>, len: usize, wire: Wire) -> Option<()> {
+    if wire.wide_len {
+        out.extend((len as u64).to_le_bytes());
+    } else {
+        out.extend(u32::try_from(len).ok()?.to_le_bytes());
+    }
+    Some(())
+}
+
+impl fmt::Display for TypeDesc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeDesc::Bool => f.write_str("bool"),
+            TypeDesc::U8 => f.write_str("u8"),
+            TypeDesc::U16 => f.write_str("u16"),
+            TypeDesc::U32 => f.write_str("u32"),
+            TypeDesc::U64 => f.write_str("u64"),
+            TypeDesc::U128 => f.write_str("u128"),
+            TypeDesc::I8 => f.write_str("i8"),
+            TypeDesc::I16 => f.write_str("i16"),
+            TypeDesc::I32 => f.write_str("i32"),
+            TypeDesc::I64 => f.write_str("i64"),
+            TypeDesc::I128 => f.write_str("i128"),
+            TypeDesc::F32 => f.write_str("f32"),
+            TypeDesc::F64 => f.write_str("f64"),
+            TypeDesc::String => f.write_str("string"),
+            TypeDesc::Vec(item) => write!(f, "vec<{item}>"),
+            TypeDesc::Option(inner) => write!(f, "option<{inner}>"),
+            TypeDesc::Array(item, len) => write!(f, "[{item}; {len}]"),
+            TypeDesc::Tuple(items) => {
+                f.write_str("(")?;
+                write_list(f, items)?;
+                f.write_str(")")
+            }
+            TypeDesc::Struct(fields) => write_fields(f, fields),
+            TypeDesc::Enum(variants) => {
+                f.write_str("enum { ")?;
+                for (i, (name, payload)) in variants.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_str(name)?;
+                    match payload {
+                        None => {}
+                        Some(TypeDesc::Tuple(items)) if !items.is_empty() => {
+                            f.write_str("(")?;
+                            write_list(f, items)?;
+                            f.write_str(")")?;
+                        }
+                        Some(TypeDesc::Struct(fields)) => {
+                            f.write_str(" ")?;
+                            write_fields(f, fields)?;
+                        }
+                        Some(other) => write!(f, "({other})")?,
+                    }
+                }
+                f.write_str(" }")
+            }
+        }
+    }
+}
+
+fn write_list(f: &mut fmt::Formatter<'_>, items: &[TypeDesc]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
+
+fn write_fields(f: &mut fmt::Formatter<'_>, fields: &[(String, TypeDesc)]) -> fmt::Result {
+    f.write_str("{ ")?;
+    for (i, (name, field)) in fields.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{name}: {field}")?;
+    }
+    f.write_str(" }")
+}
+
+impl FromStr for TypeDesc {
+    type Err = StorageError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { src, pos: 0 };
+        let desc = parser.ty()?;
+        parser.skip_ws();
+        if parser.pos != src.len() {
+            return Err(parser.error("unexpected trailing input"));
+        }
+        Ok(desc)
+    }
+}
+
+/// Recursive-descent parser for the grammar documented on `TypeDesc`.
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, what: &str) -> StorageError {
+        StorageError::InvalidDescriptor(format!("{what} at offset {}", self.pos))
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = &self.src[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_ws();
+        if self.src[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), StorageError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{c}`")))
+        }
+    }
+
+    fn word(&mut self) -> &'a str {
+        self.skip_ws();
+        let rest = &self.src[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn ident(&mut self) -> Result<String, StorageError> {
+        match self.word() {
+            "" => Err(self.error("expected a name")),
+            w if w.starts_with(|c: char| c.is_ascii_digit()) => Err(self.error("expected a name")),
+            w => Ok(w.into()),
+        }
+    }
+
+    /// Items up to `close`, separated by commas; a trailing comma is allowed.
+    fn list<X>(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<X, StorageError>,
+    ) -> Result<Vec<X>, StorageError> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(item(self)?);
+            if !self.eat(',') {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn fields(&mut self) -> Result<Vec<(String, TypeDesc)>, StorageError> {
+        self.list('}', |p| {
+            let name = p.ident()?;
+            p.expect(':')?;
+            Ok((name, p.ty()?))
+        })
+    }
+
+    fn ty(&mut self) -> Result<TypeDesc, StorageError> {
+        if self.eat('[') {
+            let item = self.ty()?;
+            self.expect(';')?;
+            let len = self
+                .word()
+                .parse()
+                .map_err(|_| self.error("expected an array length"))?;
+            self.expect(']')?;
+            return Ok(TypeDesc::Array(Box::new(item), len));
+        }
+        if self.eat('(') {
+            return Ok(TypeDesc::Tuple(self.list(')', Self::ty)?));
+        }
+        if self.eat('{') {
+            return Ok(TypeDesc::Struct(self.fields()?));
+        }
+        let start = self.pos;
+        Ok(match self.word() {
+            "bool" => TypeDesc::Bool,
+            "u8" => TypeDesc::U8,
+            "u16" => TypeDesc::U16,
+            "u32" => TypeDesc::U32,
+            "u64" => TypeDesc::U64,
+            "u128" => TypeDesc::U128,
+            "i8" => TypeDesc::I8,
+            "i16" => TypeDesc::I16,
+            "i32" => TypeDesc::I32,
+            "i64" => TypeDesc::I64,
+            "i128" => TypeDesc::I128,
+            "f32" => TypeDesc::F32,
+            "f64" => TypeDesc::F64,
+            "string" => TypeDesc::String,
+            "bytes" => TypeDesc::Vec(Box::new(TypeDesc::U8)),
+            "vec" | "option" => {
+                let wrapper = &self.src[start..self.pos];
+                self.expect('<')?;
+                let inner = Box::new(self.ty()?);
+                self.expect('>')?;
+                if wrapper == "vec" {
+                    TypeDesc::Vec(inner)
+                } else {
+                    TypeDesc::Option(inner)
+                }
+            }
+            "enum" => {
+                self.expect('{')?;
+                TypeDesc::Enum(self.list('}', Self::variant)?)
+            }
+            "" => return Err(self.error("expected a type")),
+            other => {
+                self.pos = start;
+                return Err(self.error(&format!("unknown type `{other}`")));
+            }
+        })
+    }
+
+    fn variant(&mut self) -> Result<(String, Option<TypeDesc>), StorageError> {
+        let name = self.ident()?;
+        let payload = if self.eat('(') {
+            let mut items = self.list(')', Self::ty)?;
+            if items.len() == 1 {
+                items.pop()
+            } else {
+                Some(TypeDesc::Tuple(items))
+            }
+        } else if self.eat('{') {
+            Some(TypeDesc::Struct(self.fields()?))
+        } else {
+            None
+        };
+        Ok((name, payload))
+    }
+}
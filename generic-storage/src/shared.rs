@@ -0,0 +1,127 @@
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use tokio::sync::watch;
+
+use crate::{Backend, MemoryBackend, Serializer, Storage, StorageError};
+
+/// A cloneable, thread-safe handle to one `Storage`.
+///
+/// Loads share a read lock, so many threads decode at once; saves and clears take the write
+/// lock. Every successful write bumps a generation counter published on a watch channel,
+/// so subscribers can wait for changes and re-`load`.
+pub struct SharedStorage<T, S, B = MemoryBackend>
+where
+    S: Serializer<T>,
+    B: Backend,
+{
+    inner: Arc<Shared<T, S, B>>,
+}
+
+struct Shared<T, S, B>
+where
+    S: Serializer<T>,
+    B: Backend,
+{
+    storage: RwLock<Storage<T, S, B>>,
+    changes: watch::Sender<u64>,
+}
+
+impl<T, S, B> SharedStorage<T, S, B>
+where
+    S: Serializer<T>,
+    B: Backend,
+{
+    pub fn new(storage: Storage<T, S, B>) -> Self {
+        let (changes, _) = watch::channel(0);
+        SharedStorage {
+            inner: Arc::new(Shared {
+                storage: RwLock::new(storage),
+                changes,
+            }),
+        }
+    }
+
+    pub fn save(&self, value: &T) -> Result<(), StorageError> {
+        self.write().save(value)?;
+        self.notify();
+        Ok(())
+    }
+
+    pub fn load(&self) -> Result<T, StorageError> {
+        self.read().load()
+    }
+
+    pub fn has_data(&self) -> bool {
+        self.read().has_data()
+    }
+
+    pub fn clear(&self) -> Result<(), StorageError> {
+        self.write().clear()?;
+        self.notify();
+        Ok(())
+    }
+
+    /// `Storage::update` under the write lock, so concurrent updates never lose a write.
+    pub fn update<R, E>(&self, f: impl FnOnce(&mut T) -> Result<R, E>) -> Result<R, E>
+    where
+        E: From<StorageError>,
+    {
+        let out = self.write().update(f)?;
+        self.notify();
+        Ok(out)
+    }
+
+    /// `Storage::rollback` under the write lock.
+    pub fn rollback(&self, n: usize) -> Result<(), StorageError> {
+        self.write().rollback(n)?;
+        if n > 0 {
+            self.notify();
+        }
+        Ok(())
+    }
+
+    /// A receiver whose value is the number of writes so far; `changed().await` wakes on
+    /// the next save or clear.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.inner.changes.subscribe()
+    }
+
+    /// Runs `f` with shared access to the underlying storage, e.g. for `load_ref`.
+    pub fn with_storage<R>(&self, f: impl FnOnce(&Storage<T, S, B>) -> R) -> R {
+        f(&self.read())
+    }
+
+    // A panic while a lock was held cannot leave the storage half-written (backends write
+    // atomically), so a poisoned lock is recovered rather than propagated.
+    fn read(&self) -> RwLockReadGuard<'_, Storage<T, S, B>> {
+        self.inner
+            .storage
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Storage<T, S, B>> {
+        self.inner
+            .storage
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn notify(&self) {
+        self.inner
+            .changes
+            .send_modify(|generation| *generation += 1);
+    }
+}
+
+impl<T, S, B> Clone for SharedStorage<T, S, B>
+where
+    S: Serializer<T>,
+    B: Backend,
+{
+    fn clone(&self) -> Self {
+        SharedStorage {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Property-based checks that any `Serializer<T>` can be run through, built on `proptest`.
+//! Enable the `proptest` feature to use them from another crate's tests:
+//!
+//! ```ignore
+//! use generic_storage::testing::{f64_edge_cases, Harness};
+//!
+//! let people = (any::<String>(), any::<u32>(), f64_edge_cases())
+//!     .prop_map(|(name, age, balance)| Person { name, age, balance });
+//! Harness::new(BorshSer).check(people);
+//! ```
+
+use core::fmt::Debug;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+
+use crate::Serializer;
+
+/// Runs a serializer through round-trip, `serialized_len`, truncation, trailing-garbage and
+/// random-input properties for every value a strategy generates.
+///
+/// Values are compared by re-encoding rather than `PartialEq`, so `NaN` round-trips count as
+/// long as the bits come back. Values the serializer refuses to encode (e.g. Borsh and `NaN`)
+/// are skipped, but a strategy that is rejected too often fails the run.
+#[derive(Debug, Clone)]
+pub struct Harness<S> {
+    serializer: S,
+    config: Config,
+    allow_trailing: bool,
+}
+
+impl<S> Harness<S> {
+    pub fn new(serializer: S) -> Self {
+        Harness {
+            serializer,
+            config: Config::default(),
+            allow_trailing: false,
+        }
+    }
+
+    /// Number of generated values per property (256 by default).
+    pub fn cases(mut self, cases: u32) -> Self {
+        self.config.cases = cases;
+        self
+    }
+
+    /// For formats that stop after one value (Wincode, bincode, postcard, ...): trailing bytes
+    /// may be ignored instead of rejected, but must never change the decoded value.
+    pub fn allow_trailing_bytes(mut self) -> Self {
+        self.allow_trailing = true;
+        self
+    }
+
+    /// Checks every property and panics with the shrunk counterexample on failure.
+    ///
+    /// The truncation property assumes no strict prefix of an encoding is itself a valid
+    /// encoding, which holds for structs but not for e.g. a bare JSON number.
+    pub fn check<T: Debug>(&self, values: impl Strategy<Value = T>)
+    where
+        S: Serializer<T>,
+    {
+        let mut runner = TestRunner::new(self.config.clone());
+        let garbage = (0x80u8.., vec(any::<u8>(), 0..16));
+        let result = runner.run(&(values, any::<prop::sample::Index>(), garbage), |input| {
+            let (value, cut, (first, rest)) = input;
+            let bytes = self
+                .serializer
+                .to_bytes(&value)
+                .map_err(|e| TestCaseError::reject(format!("not encodable: {e}")))?;
+            let len = self
+                .serializer
+                .serialized_len(&value)
+                .map_err(|e| TestCaseError::fail(format!("serialized_len failed: {e}")))?;
+            prop_assert_eq!(len, bytes.len(), "serialized_len disagrees with to_bytes");
+            self.round_trip(&bytes)?;
+            self.truncated(&bytes, cut)?;
+            self.trailing(&bytes, first, &rest)?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            panic!("serializer property failed: {e}");
+        }
+
+        // Arbitrary bytes may decode or fail, but must not panic.
+        let mut runner = TestRunner::new(self.config.clone());
+        let result = runner.run(&vec(any::<u8>(), 0..256), |bytes| {
+            let _ = self.serializer.from_bytes(&bytes);
+            Ok(())
+        });
+        if let Err(e) = result {
+            panic!("decoding random bytes panicked: {e}");
+        }
+    }
+
+    fn round_trip<T>(&self, bytes: &[u8]) -> Result<(), TestCaseError>
+    where
+        S: Serializer<T>,
+    {
+        let decoded = self
+            .serializer
+            .from_bytes(bytes)
+            .map_err(|e| TestCaseError::fail(format!("encoded value does not decode: {e}")))?;
+        let again = self
+            .serializer
+            .to_bytes(&decoded)
+            .map_err(|e| TestCaseError::fail(format!("decoded value does not re-encode: {e}")))?;
+        prop_assert_eq!(bytes, &again[..], "value changed across a round trip");
+        Ok(())
+    }
+
+    fn truncated<T>(&self, bytes: &[u8], cut: prop::sample::Index) -> Result<(), TestCaseError>
+    where
+        S: Serializer<T>,
+    {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let len = cut.index(bytes.len());
+        prop_assert!(
+            self.serializer.from_bytes(&bytes[..len]).is_err(),
+            "a {}-byte prefix of a {}-byte encoding decoded",
+            len,
+            bytes.len()
+        );
+        Ok(())
+    }
+
+    fn trailing<T>(&self, bytes: &[u8], first: u8, rest: &[u8]) -> Result<(), TestCaseError>
+    where
+        S: Serializer<T>,
+    {
+        let mut padded = bytes.to_vec();
+        padded.push(first);
+        padded.extend_from_slice(rest);
+        match self.serializer.from_bytes(&padded) {
+            Err(_) => Ok(()),
+            Ok(_) if !self.allow_trailing => Err(TestCaseError::fail(
+                "trailing garbage was silently accepted",
+            )),
+            Ok(decoded) => {
+                let again = self
+                    .serializer
+                    .to_bytes(&decoded)
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+                prop_assert_eq!(bytes, &again[..], "trailing garbage changed the value");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Any `f64`, weighted towards the values serializers get wrong: `NaN`, `-0.0`,
+/// infinities, subnormals and the extremes.
+pub fn f64_edge_cases() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        1 => Just(f64::NAN),
+        1 => Just(f64::INFINITY),
+        1 => Just(f64::NEG_INFINITY),
+        3 => finite_f64_edge_cases(),
+    ]
+}
+
+/// Like `f64_edge_cases` without `NaN` and infinities, for values that must always encode.
+pub fn finite_f64_edge_cases() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        Just(0.0),
+        Just(-0.0),
+        Just(f64::MIN_POSITIVE),
+        Just(f64::MIN_POSITIVE / 2.0),
+        Just(f64::MAX),
+        Just(f64::MIN),
+        Just(f64::EPSILON),
+        prop::num::f64::NORMAL | prop::num::f64::SUBNORMAL | prop::num::f64::ZERO,
+    ]
+}
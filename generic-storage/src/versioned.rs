@@ -0,0 +1,123 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::{Format, Serializer, StorageError};
+
+type Migration = Box<dyn Fn(&[u8]) -> Result<Vec<u8>, StorageError>>;
+
+/// Size of the header `Versioned` puts in front of every payload:
+/// one format tag byte followed by the schema version as a little-endian u32.
+pub const VERSION_HEADER_LEN: usize = 5;
+
+/// Wraps a `Serializer` so every payload carries its format tag and schema version.
+/// Older payloads are upgraded on read by chaining the registered migrations
+/// (v1 -> v2 -> ... -> current) before the final decode into `T`.
+pub struct Versioned<T, S> {
+    inner: S,
+    version: u32,
+    migrations: BTreeMap<u32, Migration>,
+    _marker: PhantomData<T>,
+}
+
+impl<T, S> Versioned<T, S>
+where
+    S: Serializer<T>,
+{
+    /// `version` is the schema version of `T`, written into every new payload.
+    pub fn new(inner: S, version: u32) -> Self {
+        Versioned {
+            inner,
+            version,
+            migrations: BTreeMap::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Registers how to upgrade a version `from` payload (decoded as `Old`) into version `from + 1` (`New`).
+    /// Both sides use the wrapped serializer.
+    pub fn migrate<Old, New>(mut self, from: u32, upgrade: impl Fn(Old) -> New + 'static) -> Self
+    where
+        S: Serializer<Old> + Serializer<New> + Clone + 'static,
+    {
+        let serializer = self.inner.clone();
+        self.migrations.insert(
+            from,
+            Box::new(move |bytes| {
+                let old: Old = serializer.from_bytes(bytes)?;
+                serializer.to_bytes(&upgrade(old))
+            }),
+        );
+        self
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+/// Splits a `Versioned` payload into its format tag, schema version and body.
+pub fn read_version_header(bytes: &[u8]) -> Result<(u8, u32, &[u8]), StorageError> {
+    if bytes.len() < VERSION_HEADER_LEN {
+        return Err(StorageError::Malformed("missing version header"));
+    }
+    let (header, body) = bytes.split_at(VERSION_HEADER_LEN);
+    let version = u32::from_le_bytes([header[1], header[2], header[3], header[4]]);
+    Ok((header[0], version, body))
+}
+
+impl<T, S> Serializer<T> for Versioned<T, S>
+where
+    S: Serializer<T>,
+{
+    fn to_bytes(&self, data: &T) -> Result<Vec<u8>, StorageError> {
+        let body = self.inner.to_bytes(data)?;
+        let mut out = Vec::with_capacity(VERSION_HEADER_LEN + body.len());
+        out.push(self.inner.format().id());
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.extend_from_slice(&body);
+        Ok(out)
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<T, StorageError> {
+        let (tag, mut version, body) = read_version_header(bytes)?;
+        let expected = self.inner.format();
+        if tag != expected.id() {
+            return Err(StorageError::FormatMismatch {
+                expected,
+                found: tag,
+            });
+        }
+        if version > self.version {
+            return Err(StorageError::VersionTooNew {
+                found: version,
+                current: self.version,
+            });
+        }
+
+        let mut body = Cow::Borrowed(body);
+        while version < self.version {
+            let migration = self
+                .migrations
+                .get(&version)
+                .ok_or(StorageError::MissingMigration { from: version })?;
+            body = Cow::Owned(migration(&body)?);
+            version += 1;
+        }
+        self.inner.from_bytes(&body)
+    }
+
+    fn format(&self) -> Format {
+        self.inner.format()
+    }
+
+    fn serialized_len(&self, data: &T) -> Result<usize, StorageError> {
+        Ok(VERSION_HEADER_LEN + self.inner.serialized_len(data)?)
+    }
+}
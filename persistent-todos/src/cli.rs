@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::date::Date;
+use crate::export::Format;
+use crate::recurrence::Recurrence;
+use crate::store::DEFAULT_LIST;
+use crate::todo::Priority;
+
+/// A persistent todo queue stored in a Borsh-encoded file.
+#[derive(Debug, Parser)]
+#[command(name = "todo", version)]
+pub struct Cli {
+    /// Queue file to read and write.
+    #[arg(long, short, global = true, default_value = "todo.bin")]
+    pub file: PathBuf,
+
+    /// Named list to work on.
+    #[arg(long, short, global = true, default_value = DEFAULT_LIST)]
+    pub list: String,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Add a task to the back of the queue.
+    Add {
+        /// Task description; several words need no quotes.
+        #[arg(required = true, num_args = 1..)]
+        description: Vec<String>,
+        /// low, medium or high.
+        #[arg(long, short)]
+        priority: Option<Priority>,
+        /// Due date as YYYY-MM-DD.
+        #[arg(long, short)]
+        due: Option<Date>,
+        /// Tag the task; repeat for several tags.
+        #[arg(long = "tag", short, value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// daily, weekly, monthly or a cron rule such as "* * mon-fri"; `done` then enqueues
+        /// the next occurrence.
+        #[arg(long, short)]
+        repeat: Option<Recurrence>,
+    },
+    /// List pending tasks in queue order.
+    List(ListArgs),
+    /// Show every list and how many tasks it holds.
+    Lists,
+    /// Show the most urgent task without completing it.
+    Next,
+    /// Complete a task: the given id, or the most urgent one.
+    Done { id: Option<u64> },
+    /// Delete a task without completing it.
+    Remove { id: u64 },
+    /// Move a task to the back of another list.
+    Move {
+        id: u64,
+        /// Destination list, created if it does not exist.
+        to: String,
+    },
+    /// Change a task's description, priority, due date, tags or recurrence.
+    Edit {
+        id: u64,
+        /// New description; several words need no quotes.
+        description: Vec<String>,
+        /// low, medium or high.
+        #[arg(long, short)]
+        priority: Option<Priority>,
+        /// Due date as YYYY-MM-DD.
+        #[arg(long, short)]
+        due: Option<Date>,
+        /// Add a tag; repeat for several tags.
+        #[arg(long = "tag", short, value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// Remove a tag; repeat for several tags.
+        #[arg(long = "untag", short, value_parser = parse_tag)]
+        untags: Vec<String>,
+        /// daily, weekly, monthly or a cron rule such as "* * mon-fri".
+        #[arg(long, short, conflicts_with = "no_repeat")]
+        repeat: Option<Recurrence>,
+        /// Stop the task from recurring.
+        #[arg(long)]
+        no_repeat: bool,
+    },
+    /// Delete every pending task in the list.
+    Clear,
+    /// List completed tasks, most recent first.
+    History,
+    /// Return the most recently completed task to the front of the queue.
+    Undo,
+    /// Write pending tasks as JSON, CSV or a Markdown checklist.
+    Export {
+        /// json, csv or md.
+        #[arg(long, default_value = "json")]
+        format: Format,
+        /// File to write instead of stdout.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Every list instead of just the selected one.
+        #[arg(long, short)]
+        all: bool,
+    },
+    /// Add tasks from a JSON or CSV export. Each task goes to the list it names, or the
+    /// selected one; ids already in use (or u64::MAX - 1 and up) are replaced with the list's
+    /// next id.
+    Import {
+        #[arg(value_name = "FILE")]
+        input: PathBuf,
+        /// json or csv; guessed from the file extension by default.
+        #[arg(long)]
+        format: Option<Format>,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Only tasks with this tag.
+    #[arg(long, short, value_parser = parse_tag)]
+    pub tag: Option<String>,
+    /// Only tasks whose description or tags contain this text, ignoring case.
+    #[arg(long, short)]
+    pub search: Option<String>,
+    /// Only tasks added on or after this date (YYYY-MM-DD).
+    #[arg(long)]
+    pub since: Option<Date>,
+    /// Order within each list: created, priority or due. Queue order by default.
+    #[arg(long)]
+    pub sort: Option<SortKey>,
+    /// Show at most this many tasks.
+    #[arg(long, short = 'n')]
+    pub limit: Option<usize>,
+    /// Every list instead of just the selected one.
+    #[arg(long, short)]
+    pub all: bool,
+    /// Print the tasks as a JSON array, in the same shape as `export`.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Oldest first.
+    Created,
+    /// Highest first; unset counts as medium.
+    Priority,
+    /// Earliest first; undated last.
+    Due,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "created" => Ok(SortKey::Created),
+            "priority" => Ok(SortKey::Priority),
+            "due" => Ok(SortKey::Due),
+            _ => Err(format!(
+                "invalid sort '{s}', expected created, priority or due"
+            )),
+        }
+    }
+}
+
+/// Tags are single words; a leading `#` is accepted and dropped.
+pub fn parse_tag(s: &str) -> Result<String, String> {
+    let tag = s.strip_prefix('#').unwrap_or(s);
+    if tag.is_empty() || tag.contains(char::is_whitespace) {
+        return Err(format!("invalid tag '{s}', expected a single word"));
+    }
+    Ok(tag.to_string())
+}
@@ -0,0 +1,194 @@
+use std::fmt;
+use std::str::FromStr;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// A calendar day, written and parsed as `YYYY-MM-DD`.
+///
+/// Fields are ordered year, month, day so the derived `Ord` is chronological.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, BorshSerialize, BorshDeserialize)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// The UTC day containing the unix timestamp `secs`.
+    pub fn from_unix(secs: u64) -> Self {
+        Date::from_days((secs / SECS_PER_DAY) as i64)
+    }
+
+    /// Unix timestamp of the start of the day, UTC.
+    pub fn to_unix(self) -> u64 {
+        self.to_days().max(0) as u64 * SECS_PER_DAY
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Day of the week, 0 for Sunday through 6 for Saturday.
+    pub fn weekday(&self) -> u8 {
+        // 1970-01-01 was a Thursday.
+        (self.to_days() + 4).rem_euclid(7) as u8
+    }
+
+    pub fn add_days(&self, days: i64) -> Self {
+        Date::from_days(self.to_days() + days)
+    }
+
+    /// The same day `months` months later, clamped to the end of shorter months
+    /// (2026-01-31 plus one month is 2026-02-28).
+    pub fn add_months(&self, months: u32) -> Self {
+        let index = u32::from(self.month - 1) + months;
+        let year = self.year + (index / 12) as u16;
+        let month = (index % 12) as u8 + 1;
+        let day = self.day.min(days_in_month(year, month));
+        Date { year, month, day }
+    }
+
+    /// Day `day` of the same month, clamped to the end of the month.
+    pub fn with_day(&self, day: u8) -> Self {
+        let day = day.clamp(1, days_in_month(self.year, self.month));
+        Date { day, ..*self }
+    }
+
+    /// Days since 1970-01-01, using Howard Hinnant's `days_from_civil`.
+    fn to_days(self) -> i64 {
+        let month = i64::from(self.month);
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let mp = (month + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// The date `days` days after 1970-01-01, using Howard Hinnant's `civil_from_days`.
+    fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Date {
+            year: year as u16,
+            month,
+            day,
+        }
+    }
+}
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// A rough, compact age for `elapsed` seconds: "just now", "5m", "3h", "2d", "4mo", "1y".
+pub fn format_age(elapsed: u64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    match elapsed {
+        0..MINUTE => "just now".to_string(),
+        MINUTE..HOUR => format!("{}m ago", elapsed / MINUTE),
+        HOUR..SECS_PER_DAY => format!("{}h ago", elapsed / HOUR),
+        _ => match elapsed / SECS_PER_DAY {
+            days @ 0..30 => format!("{days}d ago"),
+            days @ 30..365 => format!("{}mo ago", days / 30),
+            days => format!("{}y ago", days / 365),
+        },
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid date '{s}', expected YYYY-MM-DD");
+        let mut parts = s.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+        let year = year.parse().map_err(|_| invalid())?;
+        let month = month.parse().map_err(|_| invalid())?;
+        let day = day.parse().map_err(|_| invalid())?;
+        Date::new(year, month, day).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_unix_matches_the_calendar() {
+        for (secs, expected) in [
+            (0, "1970-01-01"),
+            (951_782_400, "2000-02-29"),
+            (1_793_491_200 + SECS_PER_DAY - 1, "2026-11-01"),
+            (4_107_542_400, "2100-03-01"),
+        ] {
+            let date: Date = expected.parse().unwrap();
+            assert_eq!(Date::from_unix(secs), date);
+            assert_eq!(date.to_days(), (secs / SECS_PER_DAY) as i64);
+        }
+    }
+
+    #[test]
+    fn arithmetic_crosses_month_and_year_ends() {
+        let date = |s: &str| s.parse::<Date>().unwrap();
+        assert_eq!(date("2026-12-31").add_days(1), date("2027-01-01"));
+        assert_eq!(date("2028-03-01").add_days(-1), date("2028-02-29"));
+        assert_eq!(date("2026-01-31").add_months(1), date("2026-02-28"));
+        assert_eq!(date("2026-11-30").add_months(3), date("2027-02-28"));
+        assert_eq!(date("2026-10-18").weekday(), 0);
+        assert_eq!(date("2026-10-18").to_unix(), 1_792_281_600);
+    }
+
+    #[test]
+    fn ages() {
+        assert_eq!(format_age(59), "just now");
+        assert_eq!(format_age(60 * 45), "45m ago");
+        assert_eq!(format_age(SECS_PER_DAY - 1), "23h ago");
+        assert_eq!(format_age(SECS_PER_DAY * 3 + 5), "3d ago");
+        assert_eq!(format_age(SECS_PER_DAY * 61), "2mo ago");
+        assert_eq!(format_age(SECS_PER_DAY * 800), "2y ago");
+    }
+}
@@ -0,0 +1,265 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::parse_tag;
+use crate::queue::Queue;
+use crate::recurrence::Recurrence;
+use crate::todo::Todo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+    /// A `- [ ]` checklist; export only.
+    Markdown,
+}
+
+impl Format {
+    /// The format a file's extension suggests, if any.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "md" | "markdown" => Ok(Format::Markdown),
+            _ => Err(format!("invalid format '{s}', expected json, csv or md")),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Markdown => "md",
+        })
+    }
+}
+
+/// One exported task. Priorities, dates, tags and recurrences are kept as the strings the CLI
+/// accepts, so the files stay easy to edit by hand or in a spreadsheet. Everything but the
+/// description is optional on import.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Record {
+    #[serde(default)]
+    pub id: Option<u64>,
+    #[serde(default)]
+    pub list: Option<String>,
+    pub description: String,
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<String>,
+}
+
+impl Record {
+    pub fn new(list: &str, todo: &Todo) -> Self {
+        Record {
+            id: Some(todo.id),
+            list: Some(list.to_string()),
+            description: todo.description.clone(),
+            created_at: Some(todo.created_at),
+            priority: todo.priority.map(|priority| priority.to_string()),
+            due: todo.due.map(|due| due.to_string()),
+            tags: todo.tags.clone(),
+            repeat: todo.recurrence.as_ref().map(|r| r.rule().to_string()),
+        }
+    }
+
+    /// Builds the task, stamped `now` unless the record has its own `created_at`. The id is
+    /// the record's, or 0 when it has none; the caller assigns the real one.
+    pub fn to_todo(&self, now: u64) -> Result<Todo, String> {
+        if self.description.trim().is_empty() {
+            return Err("empty description".to_string());
+        }
+        let due = self.due.as_deref().map(str::parse).transpose()?;
+        let mut recurrence: Option<Recurrence> =
+            self.repeat.as_deref().map(str::parse).transpose()?;
+        if let (Some(recurrence), Some(due)) = (&mut recurrence, due) {
+            recurrence.anchor(due);
+        }
+        let mut todo = Todo {
+            id: self.id.unwrap_or(0),
+            description: self.description.clone(),
+            created_at: self.created_at.unwrap_or(now),
+            priority: self.priority.as_deref().map(str::parse).transpose()?,
+            due,
+            tags: Vec::new(),
+            recurrence,
+        };
+        for tag in &self.tags {
+            todo.tag(parse_tag(tag)?);
+        }
+        Ok(todo)
+    }
+}
+
+/// `Record` as a CSV row: tags share one space-separated column.
+#[derive(Debug, Serialize, Deserialize)]
+struct Row {
+    id: Option<u64>,
+    list: Option<String>,
+    description: String,
+    created_at: Option<u64>,
+    priority: Option<String>,
+    due: Option<String>,
+    tags: Option<String>,
+    repeat: Option<String>,
+}
+
+impl From<Record> for Row {
+    fn from(record: Record) -> Self {
+        Row {
+            id: record.id,
+            list: record.list,
+            description: record.description,
+            created_at: record.created_at,
+            priority: record.priority,
+            due: record.due,
+            tags: Some(record.tags.join(" ")),
+            repeat: record.repeat,
+        }
+    }
+}
+
+impl From<Row> for Record {
+    fn from(row: Row) -> Self {
+        Record {
+            id: row.id,
+            list: row.list,
+            description: row.description,
+            created_at: row.created_at,
+            priority: row.priority,
+            due: row.due,
+            tags: row
+                .tags
+                .iter()
+                .flat_map(|tags| tags.split_whitespace())
+                .map(str::to_string)
+                .collect(),
+            repeat: row.repeat,
+        }
+    }
+}
+
+/// Writes the pending tasks of `lists` in queue order.
+pub fn write<'a>(
+    format: Format,
+    lists: &[(&'a str, &'a Queue<Todo>)],
+    mut out: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let records = lists
+        .iter()
+        .flat_map(|(name, queue)| queue.iter().map(|todo| Record::new(name, todo)));
+    match format {
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, &records.collect::<Vec<_>>())?;
+            writeln!(out)?;
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for record in records {
+                writer.serialize(Row::from(record))?;
+            }
+            writer.flush()?;
+        }
+        Format::Markdown => {
+            for (index, (name, queue)) in lists.iter().enumerate() {
+                // Headings only matter once there is more than one list.
+                if lists.len() > 1 {
+                    if index > 0 {
+                        writeln!(out)?;
+                    }
+                    writeln!(out, "## {name}\n")?;
+                }
+                for todo in queue.iter() {
+                    writeln!(out, "- [ ] {todo}")?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads records written by `write` (or by hand) in JSON or CSV.
+pub fn read(format: Format, input: impl Read) -> Result<Vec<Record>, Box<dyn Error>> {
+    match format {
+        Format::Json => Ok(serde_json::from_reader(input)?),
+        Format::Csv => csv::Reader::from_reader(input)
+            .deserialize::<Row>()
+            .map(|row| Ok(row?.into()))
+            .collect(),
+        Format::Markdown => Err("markdown export is one-way; import JSON or CSV instead".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_and_csv_round_trip() {
+        let mut queue = Queue::new();
+        for record in [
+            r#"{"id": 3, "description": "Ship, \"soon\"", "priority": "high", "due": "2026-11-01",
+                "tags": ["release", "ui"], "repeat": "1,15 * *"}"#,
+            r#"{"description": "plain"}"#,
+        ] {
+            let record: Record = serde_json::from_str(record).unwrap();
+            queue.enqueue(record.to_todo(42).unwrap());
+        }
+
+        for format in [Format::Json, Format::Csv] {
+            let mut bytes = Vec::new();
+            write(format, &[("work", &queue)], &mut bytes).unwrap();
+            let records = read(format, &bytes[..]).unwrap();
+            let again: Vec<_> = records.iter().map(|r| r.to_todo(0).unwrap()).collect();
+            assert_eq!(
+                again.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                queue.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "{format}"
+            );
+            assert!(records.iter().all(|r| r.list.as_deref() == Some("work")));
+            assert!(again.iter().all(|todo| todo.created_at == 42));
+        }
+    }
+
+    #[test]
+    fn imported_tags_are_checked_like_the_cli() {
+        let record = |tags: &str| -> Record {
+            serde_json::from_str(&format!(r#"{{"description": "x", "tags": {tags}}}"#)).unwrap()
+        };
+        let todo = record(r##"["#ui", "ui", "db"]"##).to_todo(0).unwrap();
+        assert_eq!(todo.tags, ["ui", "db"]);
+        for bad in [r#"[""]"#, r##"["#"]"##, r#"["two words"]"#] {
+            assert!(record(bad).to_todo(0).is_err(), "{bad}");
+        }
+
+        let csv = "description,tags
+x,#ui ui db
+y,#
+";
+        let records = read(Format::Csv, csv.as_bytes()).unwrap();
+        assert_eq!(records[0].to_todo(0).unwrap().tags, ["ui", "db"]);
+        assert!(records[1].to_todo(0).is_err());
+    }
+}
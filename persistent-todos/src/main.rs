@@ -1,63 +1,399 @@
+mod cli;
+mod date;
+mod export;
 mod queue;
+mod recurrence;
+mod store;
 mod todo;
 
-use std::time::SystemTime;
+use std::cmp::Reverse;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use queue::Queue;
+use clap::Parser;
+
+use cli::{Cli, Command, ListArgs, SortKey};
+use date::{Date, format_age};
+use export::{Format, Record};
+use store::Store;
 use todo::Todo;
 
-fn main() {
-    let mut queue: Queue<Todo> = Queue::load().unwrap_or_else(|_| Queue::new());
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    let args: Vec<String> = std::env::args().collect();
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let mut store = Store::load(&cli.file).map_err(|e| {
+        let mut message = format!("cannot read {}: {e}", cli.file.display());
+        let backup = store::backup_path(&cli.file);
+        if backup.exists() {
+            message += &format!("\nthe previous save is in {}", backup.display());
+        }
+        message
+    })?;
+    let list = cli.list.as_str();
 
-    match args.get(1).map(|s| s.as_str()) {
-        Some("add") => {
+    match cli.command {
+        Command::Add {
+            description,
+            priority,
+            due,
+            tags,
+            mut repeat,
+        } => {
+            let created_at = now()?;
+            // A recurring task without a due date starts at its first occurrence.
+            let due = match &repeat {
+                Some(recurrence) if due.is_none() => {
+                    recurrence.first_from(Date::from_unix(created_at))
+                }
+                _ => due,
+            };
+            if let (Some(recurrence), Some(due)) = (&mut repeat, due) {
+                recurrence.anchor(due);
+            }
+            let queue = store.list_mut(list);
             let todo = Todo {
-                id: queue.next_id,
-                created_at: SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                description: args.get(2).expect("provide a description").clone(),
+                id: queue.allocate_id()?,
+                created_at,
+                description: description.join(" "),
+                priority,
+                due,
+                tags,
+                recurrence: repeat,
             };
-            queue.next_id += 1;
+            let added = todo.to_string();
             queue.enqueue(todo);
-            queue.save().unwrap();
-            println!("Task added!");
+            store.save(&cli.file)?;
+            println!("Task added: {added}");
         }
 
-        Some("next") => match queue.peek() {
-            Some(todo) => println!("Next up: [{}] {}", todo.id, todo.description),
+        Command::Next => match store
+            .list(list)
+            .and_then(|queue| queue.peek_max_by_key(Todo::urgency))
+        {
+            Some(todo) => println!("Next up: {todo}"),
             None => println!("No tasks!"),
         },
 
-        Some("done") => {
-            if queue.is_empty() {
-                println!("No tasks to complete!");
-                return;
+        Command::Done { id: Some(id) } => {
+            let todo = store
+                .list_mut(list)
+                .remove_where(|todo| todo.id == id)
+                .ok_or_else(|| format!("no task with id {id}"))?;
+            let report = complete(&mut store, list, todo)?;
+            store.save(&cli.file)?;
+            println!("{report}");
+        }
+
+        Command::Done { id: None } => {
+            let todo = store
+                .list_mut(list)
+                .dequeue_max_by_key(Todo::urgency)
+                .ok_or("no tasks to complete")?;
+            let report = complete(&mut store, list, todo)?;
+            store.save(&cli.file)?;
+            println!("{report}");
+        }
+
+        Command::List(args) => print_list(&store, list, &args)?,
+
+        Command::Lists => {
+            if store.lists.is_empty() {
+                println!("No lists!");
             }
-            if let Some(todo) = queue.peek() {
-                println!("About to complete: {}", todo.description);
+            for (name, queue) in &store.lists {
+                if queue.is_empty() {
+                    println!("{name} (empty)");
+                } else {
+                    println!("{name} ({} task(s))", queue.len());
+                }
             }
-            match queue.dequeue() {
-                Some(todo) => println!("Completed: [{}] {}", todo.id, todo.description),
-                None => println!("No tasks!"),
+        }
+
+        Command::Remove { id } => {
+            let todo = store
+                .list_mut(list)
+                .remove_where(|todo| todo.id == id)
+                .ok_or_else(|| format!("no task with id {id}"))?;
+            store.save(&cli.file)?;
+            println!("Removed: {todo}");
+        }
+
+        Command::Move { id, to } => {
+            if to == list {
+                return Err(format!("task {id} is already in {to}").into());
+            }
+            let moved = store
+                .move_task(id, list, &to)?
+                .ok_or_else(|| format!("no task with id {id}"))?
+                .to_string();
+            store.save(&cli.file)?;
+            println!("Moved to {to}: {moved}");
+        }
+
+        Command::Edit {
+            id,
+            description,
+            priority,
+            due,
+            tags,
+            untags,
+            repeat,
+            no_repeat,
+        } => {
+            if description.is_empty()
+                && priority.is_none()
+                && due.is_none()
+                && tags.is_empty()
+                && untags.is_empty()
+                && repeat.is_none()
+                && !no_repeat
+            {
+                return Err(
+                    "nothing to change: give a description, --priority, --due, --tag, \
+                            --untag, --repeat or --no-repeat"
+                        .into(),
+                );
+            }
+            let todo = store
+                .list_mut(list)
+                .iter_mut()
+                .find(|todo| todo.id == id)
+                .ok_or_else(|| format!("no task with id {id}"))?;
+            if !description.is_empty() {
+                todo.description = description.join(" ");
+            }
+            if priority.is_some() {
+                todo.priority = priority;
+            }
+            let reanchor = due.is_some() || repeat.is_some();
+            if due.is_some() {
+                todo.due = due;
             }
-            queue.save().unwrap();
+            todo.tags.retain(|tag| !untags.contains(tag));
+            for tag in tags {
+                todo.tag(tag);
+            }
+            if repeat.is_some() || no_repeat {
+                todo.recurrence = repeat;
+            }
+            // A new due date or rule re-anchors a monthly task; other edits keep its day.
+            if reanchor && let (Some(recurrence), Some(due)) = (&mut todo.recurrence, todo.due) {
+                recurrence.anchor(due);
+            }
+            let updated = todo.to_string();
+            store.save(&cli.file)?;
+            println!("Updated: {updated}");
         }
 
-        Some("list") => {
-            if queue.is_empty() {
-                println!("No tasks!");
-                return;
+        Command::Clear => {
+            let queue = store.list_mut(list);
+            let count = queue.len();
+            queue.clear();
+            store.save(&cli.file)?;
+            println!("Cleared {count} task(s).");
+        }
+
+        Command::History => {
+            let history: Vec<_> = store.history(list).collect();
+            if history.is_empty() {
+                println!("No completed tasks!");
+                return Ok(());
             }
-            println!("{} task(s) pending:", queue.len());
-            for todo in queue.iter() {
-                println!("[{}] {}", todo.id, todo.description);
+            println!("{} task(s) completed:", history.len());
+            for completed in history {
+                println!("{completed}");
             }
         }
 
-        _ => println!("Usage: todo <add|list|done|next>"),
+        Command::Undo => {
+            let restored = store
+                .undo(list)?
+                .ok_or("no completed task to undo")?
+                .to_string();
+            store.save(&cli.file)?;
+            println!("Restored: {restored}");
+        }
+
+        Command::Export {
+            format,
+            output,
+            all,
+        } => {
+            let lists: Vec<_> = if all {
+                store
+                    .lists
+                    .iter()
+                    .map(|(name, queue)| (name.as_str(), queue))
+                    .collect()
+            } else {
+                store
+                    .lists
+                    .get_key_value(list)
+                    .map(|(name, queue)| (name.as_str(), queue))
+                    .into_iter()
+                    .collect()
+            };
+            match output {
+                Some(path) => {
+                    export::write(format, &lists, BufWriter::new(File::create(&path)?))?;
+                    let count: usize = lists.iter().map(|(_, queue)| queue.len()).sum();
+                    println!("Exported {count} task(s) to {}.", path.display());
+                }
+                None => export::write(format, &lists, io::stdout().lock())?,
+            }
+        }
+
+        Command::Import { input, format } => {
+            let format = format
+                .or_else(|| Format::from_path(&input))
+                .ok_or("cannot tell the format from the file name; pass --format")?;
+            let records = export::read(format, BufReader::new(File::open(&input)?))
+                .map_err(|e| format!("cannot read {}: {e}", input.display()))?;
+            let now = now()?;
+            // Validate everything before touching the store, so a bad row imports nothing.
+            let todos = records
+                .iter()
+                .enumerate()
+                .map(|(index, record)| {
+                    let todo = record
+                        .to_todo(now)
+                        .map_err(|e| format!("record {}: {e}", index + 1))?;
+                    let list = record.list.clone().unwrap_or_else(|| list.to_string());
+                    Ok((list, record.id, todo))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let mut report = Vec::with_capacity(todos.len());
+            for (list, id, mut todo) in todos {
+                if id.is_none() {
+                    todo.id = store.list_mut(&list).allocate_id()?;
+                }
+                let added = store.insert(&list, todo)?;
+                report.push(match id {
+                    Some(id) if id != added.id => {
+                        format!("Imported into {list} (id {id} was unavailable): {added}")
+                    }
+                    _ => format!("Imported into {list}: {added}"),
+                });
+            }
+            store.save(&cli.file)?;
+            for line in &report {
+                println!("{line}");
+            }
+            println!("Imported {} task(s).", report.len());
+        }
+    }
+    Ok(())
+}
+
+/// `list`: the selected list (or all of them) filtered, sorted and limited per `args`.
+fn print_list(store: &Store, list: &str, args: &ListArgs) -> Result<(), Box<dyn Error>> {
+    let search = args.search.as_deref().map(str::to_lowercase);
+    let since = args.since.map(|date| date.to_unix());
+    let matches = |todo: &&Todo| {
+        args.tag.as_deref().is_none_or(|tag| todo.has_tag(tag))
+            && since.is_none_or(|since| todo.created_at >= since)
+            && search.as_deref().is_none_or(|search| {
+                todo.description.to_lowercase().contains(search)
+                    || todo
+                        .tags
+                        .iter()
+                        .any(|tag| tag.to_lowercase().contains(search))
+            })
+    };
+    let lists: Vec<_> = if args.all {
+        store.lists.iter().collect()
+    } else {
+        store.lists.get_key_value(list).into_iter().collect()
+    };
+
+    let mut groups = Vec::new();
+    let mut total = 0;
+    for (name, queue) in lists {
+        let mut todos: Vec<&Todo> = queue.iter().filter(matches).collect();
+        total += todos.len();
+        // Stable sorts, so ties stay in queue order.
+        match args.sort {
+            Some(SortKey::Created) => todos.sort_by_key(|todo| todo.created_at),
+            Some(SortKey::Priority) => {
+                todos.sort_by_key(|todo| Reverse(todo.priority.unwrap_or_default()))
+            }
+            Some(SortKey::Due) => todos.sort_by_key(|todo| (todo.due.is_none(), todo.due)),
+            None => {}
+        }
+        groups.push((name, todos));
+    }
+    let mut remaining = args.limit.unwrap_or(usize::MAX);
+    for (_, todos) in &mut groups {
+        todos.truncate(remaining);
+        remaining -= todos.len();
     }
+    groups.retain(|(_, todos)| !todos.is_empty());
+
+    if args.json {
+        let records: Vec<_> = groups
+            .iter()
+            .flat_map(|(name, todos)| todos.iter().map(|todo| Record::new(name, todo)))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&records)?);
+        return Ok(());
+    }
+    let shown: usize = groups.iter().map(|(_, todos)| todos.len()).sum();
+    if shown == 0 {
+        println!("No tasks!");
+        return Ok(());
+    }
+    if shown < total {
+        println!("{shown} of {total} task(s) pending:");
+    } else {
+        println!("{total} task(s) pending:");
+    }
+    let now = now()?;
+    for (name, todos) in groups {
+        if args.all {
+            println!("{name}:");
+        }
+        for todo in todos {
+            let age = format_age(now.saturating_sub(todo.created_at));
+            println!("{todo} [added {age}]");
+        }
+    }
+    Ok(())
+}
+
+/// Archives `todo` as completed now. Returns the report to print once the store is saved: the
+/// task, along with its next occurrence if it recurs.
+fn complete(store: &mut Store, list: &str, todo: Todo) -> Result<String, Box<dyn Error>> {
+    let recurs = todo.recurrence.is_some();
+    let completed = store.complete(list, todo, now()?)?;
+    let mut report = format!("Completed: {completed}");
+    match completed.spawned {
+        Some(id) => {
+            let next = store
+                .list(list)
+                .and_then(|queue| queue.iter().find(|t| t.id == id));
+            if let Some(next) = next {
+                report += &format!("\nNext occurrence: {next}");
+            }
+        }
+        None if recurs => report += "\nNo further occurrences.",
+        None => {}
+    }
+    Ok(report)
+}
+
+/// The current unix timestamp, in seconds.
+fn now() -> Result<u64, Box<dyn Error>> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
 }
@@ -1,3 +1,6 @@
+use std::error::Error;
+use std::fmt;
+
 use borsh::{BorshDeserialize, BorshSerialize};
 
 #[derive(Debug, BorshSerialize, BorshDeserialize)]
@@ -16,10 +19,34 @@ impl<T> Queue<T> {
         }
     }
 
+    /// Hands out `next_id` and moves it on. Fails once every id is used rather than
+    /// overflowing.
+    pub fn allocate_id(&mut self) -> Result<u64, IdsExhausted> {
+        let id = self.next_id;
+        self.next_id = id.checked_add(1).ok_or(IdsExhausted)?;
+        Ok(id)
+    }
+
     pub fn enqueue(&mut self, item: T) {
         self.inbox.push(item);
     }
 
+    /// Puts `item` ahead of everything else, so it is the next one dequeued.
+    pub fn push_front(&mut self, item: T) {
+        self.outbox.push(item);
+    }
+
+    /// Converts every item, keeping their order.
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Queue<U> {
+        Queue {
+            inbox: self.inbox.into_iter().map(&mut f).collect(),
+            outbox: self.outbox.into_iter().map(&mut f).collect(),
+            next_id: self.next_id,
+        }
+    }
+
+    // Plain FIFO access; the CLI goes through the priority-aware variants below.
+    #[allow(dead_code)]
     pub fn dequeue(&mut self) -> Option<T> {
         if self.outbox.is_empty() {
             while let Some(item) = self.inbox.pop() {
@@ -29,6 +56,7 @@ impl<T> Queue<T> {
         self.outbox.pop()
     }
 
+    #[allow(dead_code)]
     pub fn peek(&mut self) -> Option<&T> {
         if self.outbox.is_empty() {
             while let Some(item) = self.inbox.pop() {
@@ -49,22 +77,139 @@ impl<T> Queue<T> {
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.outbox.iter().rev().chain(self.inbox.iter())
     }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.outbox.iter_mut().rev().chain(self.inbox.iter_mut())
+    }
+
+    pub fn clear(&mut self) {
+        self.inbox.clear();
+        self.outbox.clear();
+    }
+
+    /// Priority-aware `peek`: the first item, in FIFO order, with the greatest key.
+    pub fn peek_max_by_key<K: Ord>(&self, key: impl Fn(&T) -> K) -> Option<&T> {
+        self.position_max_by_key(key)
+            .and_then(|index| self.iter().nth(index))
+    }
+
+    /// Priority-aware `dequeue`: removes the item `peek_max_by_key` returns, leaving the
+    /// rest in FIFO order.
+    pub fn dequeue_max_by_key<K: Ord>(&mut self, key: impl Fn(&T) -> K) -> Option<T> {
+        let index = self.position_max_by_key(key)?;
+        Some(self.remove_at(index))
+    }
+
+    fn position_max_by_key<K: Ord>(&self, key: impl Fn(&T) -> K) -> Option<usize> {
+        let mut best: Option<(usize, K)> = None;
+        for (index, item) in self.iter().enumerate() {
+            let k = key(item);
+            if best.as_ref().is_none_or(|(_, max)| k > *max) {
+                best = Some((index, k));
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+
+    /// Removes and returns the first item, in FIFO order, matching `pred`.
+    pub fn remove_where(&mut self, pred: impl FnMut(&T) -> bool) -> Option<T> {
+        let index = self.iter().position(pred)?;
+        Some(self.remove_at(index))
+    }
+
+    /// Keeps only the items `keep` returns true for, visiting them in FIFO order.
+    ///
+    /// The survivors are gathered into `inbox` in order; the next `dequeue` or `peek` moves
+    /// them back to `outbox` as usual.
+    #[allow(dead_code)]
+    pub fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        let mut items: Vec<T> = self.outbox.drain(..).rev().collect();
+        items.append(&mut self.inbox);
+        items.retain(keep);
+        self.inbox = items;
+    }
+
+    /// Removes the item at `index` in FIFO order (as yielded by `iter`).
+    fn remove_at(&mut self, index: usize) -> T {
+        if index < self.outbox.len() {
+            self.outbox.remove(self.outbox.len() - 1 - index)
+        } else {
+            self.inbox.remove(index - self.outbox.len())
+        }
+    }
+}
+
+/// Returned by `Queue::allocate_id` when `next_id` has reached `u64::MAX`.
+#[derive(Debug)]
+pub struct IdsExhausted;
+
+impl fmt::Display for IdsExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("no task ids left in this list")
+    }
 }
 
-impl<T> Queue<T>
-where
-    T: BorshSerialize,
-    T: BorshDeserialize,
-{
-    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let bytes = borsh::to_vec(self)?;
-        std::fs::write("todo.bin", &bytes)?;
-        Ok(())
-    }
-
-    pub fn load() -> Result<Queue<T>, Box<dyn std::error::Error>> {
-        let bytes = std::fs::read("todo.bin")?;
-        let queue = borsh::from_slice(&bytes)?;
-        Ok(queue)
+impl Error for IdsExhausted {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dequeue_max_by_key_breaks_ties_in_fifo_order() {
+        let mut queue = Queue::new();
+        for item in [(1, 'a'), (3, 'b'), (2, 'c')] {
+            queue.enqueue(item);
+        }
+        // Split the items across both stacks.
+        assert_eq!(queue.dequeue(), Some((1, 'a')));
+        queue.enqueue((3, 'd'));
+        queue.enqueue((1, 'e'));
+
+        assert_eq!(queue.peek_max_by_key(|item| item.0), Some(&(3, 'b')));
+        assert_eq!(queue.dequeue_max_by_key(|item| item.0), Some((3, 'b')));
+        assert_eq!(queue.dequeue_max_by_key(|item| item.0), Some((3, 'd')));
+        assert_eq!(
+            queue.iter().copied().collect::<Vec<_>>(),
+            [(2, 'c'), (1, 'e')]
+        );
+        assert_eq!(queue.dequeue(), Some((2, 'c')));
+        assert_eq!(queue.dequeue(), Some((1, 'e')));
+        assert_eq!(queue.dequeue_max_by_key(|item| item.0), None);
+    }
+
+    #[test]
+    fn remove_where_and_retain_keep_fifo_order_across_stacks() {
+        let mut queue = Queue::new();
+        queue.enqueue(0);
+        queue.enqueue(1);
+        queue.enqueue(2);
+        assert_eq!(queue.peek(), Some(&0));
+        for item in 3..8 {
+            queue.enqueue(item);
+        }
+
+        assert_eq!(queue.remove_where(|&item| item == 1), Some(1));
+        assert_eq!(queue.remove_where(|&item| item == 5), Some(5));
+        assert_eq!(queue.remove_where(|&item| item == 9), None);
+        assert_eq!(
+            queue.iter().copied().collect::<Vec<_>>(),
+            [0, 2, 3, 4, 6, 7]
+        );
+
+        let mut visited = Vec::new();
+        queue.retain(|&item| {
+            visited.push(item);
+            item % 2 == 0
+        });
+        assert_eq!(visited, [0, 2, 3, 4, 6, 7]);
+        assert_eq!(queue.len(), 4);
+        assert_eq!(queue.dequeue(), Some(0));
+        queue.enqueue(8);
+        assert_eq!(queue.iter().copied().collect::<Vec<_>>(), [2, 4, 6, 8]);
+
+        queue.push_front(1);
+        assert_eq!(queue.iter().copied().collect::<Vec<_>>(), [1, 2, 4, 6, 8]);
+        assert_eq!(queue.dequeue(), Some(1));
     }
 }
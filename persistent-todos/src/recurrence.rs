@@ -0,0 +1,296 @@
+use std::fmt;
+use std::str::FromStr;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::date::Date;
+
+/// How often a task comes back once it is done.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    /// Day `day` of every month, clamped to the end of shorter months. The day is taken from
+    /// the task's due date by [`Recurrence::anchor`]; until then (`0`) it follows the date
+    /// being advanced.
+    Monthly {
+        day: u8,
+    },
+    Cron(Cron),
+}
+
+impl Recurrence {
+    /// The first occurrence strictly after `date`, or `None` if there is none in the next
+    /// eight years (which only a cron rule for 29 February across a skipped leap year hits).
+    pub fn next_after(&self, date: Date) -> Option<Date> {
+        match self {
+            Recurrence::Daily => Some(date.add_days(1)),
+            Recurrence::Weekly => Some(date.add_days(7)),
+            Recurrence::Monthly { day } => {
+                let day = if *day == 0 { date.day() } else { *day };
+                Some(date.add_months(1).with_day(day))
+            }
+            Recurrence::Cron(cron) => (1..=MAX_SEARCH_DAYS)
+                .map(|days| date.add_days(days))
+                .find(|day| cron.matches(*day)),
+        }
+    }
+
+    /// Pins a monthly rule to the day of the month of `due`, so a task due on the 31st comes
+    /// back on the 31st after passing through a shorter month.
+    pub fn anchor(&mut self, due: Date) {
+        if let Recurrence::Monthly { day } = self {
+            *day = due.day();
+        }
+    }
+
+    /// The rule as `--repeat` takes it.
+    pub fn rule(&self) -> &str {
+        match self {
+            Recurrence::Daily => "daily",
+            Recurrence::Weekly => "weekly",
+            Recurrence::Monthly { .. } => "monthly",
+            Recurrence::Cron(cron) => &cron.expr,
+        }
+    }
+
+    /// The first occurrence on or after `today`, used as the due date of a new recurring task.
+    pub fn first_from(&self, today: Date) -> Option<Date> {
+        match self {
+            Recurrence::Cron(_) => self.next_after(today.add_days(-1)),
+            _ => Some(today),
+        }
+    }
+
+    /// The due date of the occurrence after one due on `due` (or undated) that was completed
+    /// `today`. Occurrences that are already past are skipped, keeping the rule's rhythm: a
+    /// weekly task due on a Friday stays on Fridays however late it is done.
+    pub fn next_due(&self, due: Option<Date>, today: Date) -> Option<Date> {
+        let mut next = self.next_after(due.unwrap_or(today))?;
+        while next <= today {
+            next = self.next_after(next)?;
+        }
+        Some(next)
+    }
+}
+
+/// Eight years, enough to reach the next 29 February even across a skipped leap year.
+const MAX_SEARCH_DAYS: i64 = 8 * 366;
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "daily" => Ok(Recurrence::Daily),
+            "weekly" => Ok(Recurrence::Weekly),
+            "monthly" => Ok(Recurrence::Monthly { day: 0 }),
+            _ if s.split_whitespace().count() == 3 => s.parse().map(Recurrence::Cron),
+            _ => Err(format!(
+                "invalid recurrence '{s}', expected daily, weekly, monthly or a \
+                 '<day-of-month> <month> <day-of-week>' cron rule"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recurrence::Cron(_) => write!(f, "'{}'", self.rule()),
+            _ => f.write_str(self.rule()),
+        }
+    }
+}
+
+/// The date fields of a cron schedule: `<day-of-month> <month> <day-of-week>`, e.g.
+/// `* * mon-fri` or `1,15 */3 *`.
+///
+/// Fields take `*`, numbers or names, ranges `a-b`, steps `/n` and comma lists. As in cron, a
+/// day matches when its month matches and either day field does; an unrestricted (`*`) day
+/// field is ignored when the other is restricted.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Cron {
+    expr: String,
+    /// Bit `d` for day of the month `d`.
+    days: u32,
+    /// Bit `m` for month `m`.
+    months: u16,
+    /// Bit 0 for Sunday through bit 6 for Saturday.
+    weekdays: u8,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+/// The longest each month can be, so `30 2 *` is rejected but `29 2 *` is not.
+const MAX_MONTH_DAYS: [u8; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+impl Cron {
+    fn matches(&self, date: Date) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+}
+
+impl FromStr for Cron {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [days, months, weekdays] = fields[..] else {
+            return Err(format!(
+                "invalid cron rule '{s}', expected '<day-of-month> <month> <day-of-week>'"
+            ));
+        };
+        let (days, any_day) = parse_field(days, 1, 31, &[], 0)?;
+        let (months, _) = parse_field(months, 1, 12, &MONTHS, 1)?;
+        let (weekdays, any_weekday) = parse_field(weekdays, 0, 7, &WEEKDAYS, 0)?;
+        // 7 is Sunday too.
+        let weekdays = (weekdays | weekdays >> 7) & 0x7f;
+        let cron = Cron {
+            expr: fields.join(" "),
+            days,
+            months: months as u16,
+            weekdays: weekdays as u8,
+            any_day,
+            any_weekday,
+        };
+
+        let possible = !any_weekday
+            || (1..=12).any(|month| {
+                cron.months & (1 << month) != 0
+                    && (1..=MAX_MONTH_DAYS[month - 1]).any(|day| cron.days & (1 << day) != 0)
+            });
+        if !possible {
+            return Err(format!("cron rule '{s}' never matches a date"));
+        }
+        Ok(cron)
+    }
+}
+
+/// Parses one cron field into a bit mask over `min..=max`, and whether it was a bare `*`.
+/// `names[i]` is accepted for the value `i + first_name`.
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    first_name: u32,
+) -> Result<(u32, bool), String> {
+    let invalid = || format!("invalid cron field '{field}'");
+    let value = |s: &str| -> Result<u32, String> {
+        let value = match names.iter().position(|name| name.eq_ignore_ascii_case(s)) {
+            Some(index) => index as u32 + first_name,
+            None => s.parse().map_err(|_| invalid())?,
+        };
+        if (min..=max).contains(&value) {
+            Ok(value)
+        } else {
+            Err(format!(
+                "'{s}' is out of range {min}-{max} in cron field '{field}'"
+            ))
+        }
+    };
+
+    let mut mask = 0;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse().map_err(|_| invalid())?),
+            None => (item, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // `a/n` runs from `a` to the end of the field, as in cron.
+            None if item.contains('/') => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step) {
+            mask |= 1 << value;
+        }
+    }
+    Ok((mask, field == "*"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn next_due_skips_missed_occurrences_but_keeps_the_rhythm() {
+        let today = date("2026-10-18");
+        let weekly = Recurrence::Weekly;
+        assert_eq!(
+            weekly.next_due(Some(date("2026-10-16")), today),
+            Some(date("2026-10-23"))
+        );
+        assert_eq!(
+            weekly.next_due(Some(date("2026-10-20")), today),
+            Some(date("2026-10-27"))
+        );
+        assert_eq!(weekly.next_due(None, today), Some(date("2026-10-25")));
+        let mut monthly: Recurrence = "monthly".parse().unwrap();
+        monthly.anchor(date("2026-08-31"));
+        assert_eq!(
+            monthly.next_due(Some(date("2026-08-31")), today),
+            Some(date("2026-10-31"))
+        );
+        // The anchor survives a month that is too short for it.
+        assert_eq!(
+            monthly.next_due(Some(date("2027-02-28")), date("2027-02-28")),
+            Some(date("2027-03-31"))
+        );
+    }
+
+    #[test]
+    fn cron_rules() {
+        let cron = |s: &str| s.parse::<Recurrence>().unwrap();
+        // 2026-10-18 is a Sunday.
+        let today = date("2026-10-18");
+        assert_eq!(
+            cron("* * mon-fri").next_after(today),
+            Some(date("2026-10-19"))
+        );
+        assert_eq!(cron("* * 5").first_from(today), Some(date("2026-10-23")));
+        assert_eq!(cron("* * 0,7").first_from(today), Some(today));
+        assert_eq!(
+            cron("1,15 */3 *").next_after(today),
+            Some(date("2027-01-01"))
+        );
+        assert_eq!(cron("29 feb *").next_after(today), Some(date("2028-02-29")));
+        // Either day field matches when both are restricted.
+        assert_eq!(cron("20 * sat").next_after(today), Some(date("2026-10-20")));
+
+        for bad in [
+            "30 2 *",
+            "* 13 *",
+            "*/0 * *",
+            "5-1 * *",
+            "* * funday",
+            "* *",
+        ] {
+            assert!(bad.parse::<Recurrence>().is_err(), "{bad} parsed");
+        }
+    }
+}
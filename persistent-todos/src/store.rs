@@ -0,0 +1,433 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use generic_storage::{
+    Backend, BorshSer, Checksummed, ENVELOPE_MAGIC, FileBackend, Serializer, StorageError,
+    Versioned,
+};
+
+use crate::date::Date;
+use crate::queue::{IdsExhausted, Queue};
+use crate::todo::{Completed, Todo, TodoV0};
+
+/// Schema version of `Store`. Bump it when the Borsh layout changes and register a migration
+/// from the previous one in `serializer`.
+const VERSION: u32 = 3;
+
+/// The list commands use when `--list` is not given.
+pub const DEFAULT_LIST: &str = "default";
+
+/// Everything saved in the todo file: the named lists of pending tasks and the archive of
+/// completed tasks.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct Store {
+    /// Each list is its own queue with its own ids.
+    pub lists: BTreeMap<String, Queue<Todo>>,
+    /// Completed tasks from every list, oldest first.
+    pub archive: Vec<Completed>,
+}
+
+impl Store {
+    pub fn new() -> Self {
+        Store {
+            lists: BTreeMap::new(),
+            archive: Vec::new(),
+        }
+    }
+
+    pub fn list(&self, name: &str) -> Option<&Queue<Todo>> {
+        self.lists.get(name)
+    }
+
+    /// The list called `name`, created empty if it does not exist yet.
+    pub fn list_mut(&mut self, name: &str) -> &mut Queue<Todo> {
+        self.lists
+            .entry(name.to_string())
+            .or_insert_with(Queue::new)
+    }
+
+    /// Moves a finished task from `list` into the archive. A recurring task's next
+    /// occurrence is enqueued on the same list and recorded as `spawned`.
+    pub fn complete(
+        &mut self,
+        list: &str,
+        todo: Todo,
+        completed_at: u64,
+    ) -> Result<&Completed, IdsExhausted> {
+        let today = Date::from_unix(completed_at);
+        let next_due = todo
+            .recurrence
+            .as_ref()
+            .and_then(|recurrence| recurrence.next_due(todo.due, today));
+        let spawned = match next_due {
+            Some(due) => {
+                let queue = self.list_mut(list);
+                let next = Todo {
+                    id: queue.allocate_id()?,
+                    created_at: completed_at,
+                    due: Some(due),
+                    ..todo.clone()
+                };
+                let id = next.id;
+                queue.enqueue(next);
+                Some(id)
+            }
+            None => None,
+        };
+        self.archive.push(Completed {
+            todo,
+            list: list.to_string(),
+            completed_at,
+            spawned,
+        });
+        Ok(self.archive.last().unwrap())
+    }
+
+    /// Tasks completed from `list`, most recent first.
+    pub fn history<'a>(&'a self, list: &'a str) -> impl Iterator<Item = &'a Completed> {
+        self.archive.iter().rev().filter(move |c| c.list == list)
+    }
+
+    /// Puts the task most recently completed from `list` back at the front of that list.
+    pub fn undo(&mut self, list: &str) -> Result<Option<&Todo>, IdsExhausted> {
+        let Some(index) = self.archive.iter().rposition(|c| c.list == list) else {
+            return Ok(None);
+        };
+        let completed = self.archive.remove(index);
+        let queue = self.list_mut(list);
+        if let Some(id) = completed.spawned {
+            queue.remove_where(|todo| todo.id == id);
+        }
+        let todo = adopt(queue, completed.todo)?;
+        queue.push_front(todo);
+        Ok(queue.iter().next())
+    }
+
+    /// Moves task `id` from list `from` to the back of list `to`. It keeps its id unless `to`
+    /// already uses it. `Ok(None)` if `from` has no such task.
+    pub fn move_task(
+        &mut self,
+        id: u64,
+        from: &str,
+        to: &str,
+    ) -> Result<Option<&Todo>, IdsExhausted> {
+        let Some(todo) = self
+            .lists
+            .get_mut(from)
+            .and_then(|queue| queue.remove_where(|todo| todo.id == id))
+        else {
+            return Ok(None);
+        };
+        self.insert(to, todo).map(Some)
+    }
+
+    /// Adds a task from elsewhere to the back of `list`. It keeps its id unless `list`
+    /// already uses it, in which case it gets the list's `next_id`.
+    pub fn insert(&mut self, list: &str, todo: Todo) -> Result<&Todo, IdsExhausted> {
+        let queue = self.list_mut(list);
+        let todo = adopt(queue, todo)?;
+        queue.enqueue(todo);
+        Ok(queue.iter().last().unwrap())
+    }
+
+    /// Writes the store crash-safely through `FileBackend`, after copying the current file to
+    /// `backup_path(path)` the same way.
+    pub fn save(&self, path: &Path) -> Result<(), StorageError> {
+        let bytes = serializer().to_bytes(self)?;
+        let mut file = FileBackend::new(path);
+        if let Some(previous) = file.read()? {
+            FileBackend::new(backup_path(path)).write(&previous)?;
+        }
+        file.write(&bytes)
+    }
+
+    /// Reads the store at `path`, or an empty one if nothing has been saved there yet.
+    ///
+    /// Older payloads are migrated in memory and rewritten in the current version on the next
+    /// save. Anything unreadable is an error rather than an empty queue.
+    pub fn load(path: &Path) -> Result<Store, StorageError> {
+        let Some(bytes) = FileBackend::new(path).read()? else {
+            return Ok(Store::new());
+        };
+        if bytes.starts_with(&ENVELOPE_MAGIC) {
+            return serializer().from_bytes(&bytes);
+        }
+        // The bare queue saved before the envelope, which starts with the inbox length and so
+        // never with the envelope magic.
+        let queue: Queue<TodoV0> = BorshSer.from_bytes(&bytes)?;
+        Ok(Store {
+            lists: BTreeMap::from([(DEFAULT_LIST.to_string(), queue.map(Todo::from))]),
+            archive: Vec::new(),
+        })
+    }
+}
+
+/// How the store is written: Borsh, tagged with `VERSION` and wrapped in a checksummed
+/// envelope. Payloads of earlier versions are upgraded one version at a time.
+fn serializer() -> Checksummed<Versioned<Store, BorshSer>> {
+    Checksummed::new(
+        Versioned::new(BorshSer, VERSION)
+            .migrate(1, |store: legacy::StoreV1| legacy::StoreV2::from(store))
+            .migrate(2, |store: legacy::StoreV2| Store::from(store)),
+    )
+}
+
+/// Prepares a task from elsewhere for `queue`: if its id is taken it gets the next free one,
+/// and `next_id` is moved past it so the id is never handed out again. So is an id so large
+/// that `next_id` would reach `u64::MAX`, leaving no id for the next task.
+fn adopt(queue: &mut Queue<Todo>, mut todo: Todo) -> Result<Todo, IdsExhausted> {
+    let taken = queue.iter().any(|t| t.id == todo.id);
+    match todo.id.checked_add(1) {
+        Some(after) if !taken && after < u64::MAX => queue.next_id = queue.next_id.max(after),
+        _ => todo.id = queue.allocate_id()?,
+    }
+    Ok(todo)
+}
+
+/// Where `save` keeps the previous contents of `path`.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// `Store` layouts of earlier schema versions, upgraded by the migrations in `serializer`.
+mod legacy {
+    use std::collections::BTreeMap;
+
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    use super::{DEFAULT_LIST, Store};
+    use crate::date::Date;
+    use crate::queue::Queue;
+    use crate::todo::{Completed, Priority, Todo};
+
+    /// `Todo` in schema version 1, before tags.
+    #[derive(BorshSerialize, BorshDeserialize)]
+    pub(super) struct TodoV1 {
+        id: u64,
+        description: String,
+        created_at: u64,
+        priority: Option<Priority>,
+        due: Option<Date>,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    pub(super) struct CompletedV1 {
+        todo: TodoV1,
+        completed_at: u64,
+    }
+
+    /// Schema version 1: a single queue and its archive.
+    #[derive(BorshSerialize, BorshDeserialize)]
+    pub(super) struct StoreV1 {
+        queue: Queue<TodoV1>,
+        archive: Vec<CompletedV1>,
+    }
+
+    /// `Todo` in schema version 2, before recurrence.
+    #[derive(BorshSerialize, BorshDeserialize)]
+    pub(super) struct TodoV2 {
+        id: u64,
+        description: String,
+        created_at: u64,
+        priority: Option<Priority>,
+        due: Option<Date>,
+        tags: Vec<String>,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    pub(super) struct CompletedV2 {
+        todo: TodoV2,
+        list: String,
+        completed_at: u64,
+    }
+
+    /// Schema version 2: named lists and their archive.
+    #[derive(BorshSerialize, BorshDeserialize)]
+    pub(super) struct StoreV2 {
+        lists: BTreeMap<String, Queue<TodoV2>>,
+        archive: Vec<CompletedV2>,
+    }
+
+    impl From<TodoV1> for TodoV2 {
+        fn from(todo: TodoV1) -> Self {
+            TodoV2 {
+                id: todo.id,
+                description: todo.description,
+                created_at: todo.created_at,
+                priority: todo.priority,
+                due: todo.due,
+                tags: Vec::new(),
+            }
+        }
+    }
+
+    /// The single queue becomes the default list.
+    impl From<StoreV1> for StoreV2 {
+        fn from(store: StoreV1) -> Self {
+            let archive = store
+                .archive
+                .into_iter()
+                .map(|completed| CompletedV2 {
+                    todo: completed.todo.into(),
+                    list: DEFAULT_LIST.to_string(),
+                    completed_at: completed.completed_at,
+                })
+                .collect();
+            StoreV2 {
+                lists: BTreeMap::from([(DEFAULT_LIST.to_string(), store.queue.map(TodoV2::from))]),
+                archive,
+            }
+        }
+    }
+
+    impl From<TodoV2> for Todo {
+        fn from(todo: TodoV2) -> Self {
+            Todo {
+                id: todo.id,
+                description: todo.description,
+                created_at: todo.created_at,
+                priority: todo.priority,
+                due: todo.due,
+                tags: todo.tags,
+                recurrence: None,
+            }
+        }
+    }
+
+    impl From<StoreV2> for Store {
+        fn from(store: StoreV2) -> Self {
+            let lists = store
+                .lists
+                .into_iter()
+                .map(|(name, queue)| (name, queue.map(Todo::from)))
+                .collect();
+            let archive = store
+                .archive
+                .into_iter()
+                .map(|completed| Completed {
+                    todo: completed.todo.into(),
+                    list: completed.list,
+                    completed_at: completed.completed_at,
+                    spawned: None,
+                })
+                .collect();
+            Store { lists, archive }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn todo(id: u64) -> Todo {
+        Todo {
+            id,
+            description: format!("task {id}"),
+            created_at: 0,
+            priority: None,
+            due: None,
+            tags: Vec::new(),
+            recurrence: None,
+        }
+    }
+
+    fn ids(store: &Store) -> Vec<u64> {
+        store.lists[DEFAULT_LIST]
+            .iter()
+            .map(|todo| todo.id)
+            .collect()
+    }
+
+    #[test]
+    fn save_keeps_a_backup_and_load_rejects_corruption() {
+        let dir = std::env::temp_dir().join(format!("persistent-todo-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("todo.bin");
+
+        let mut store = Store::load(&path).unwrap();
+        store.list_mut(DEFAULT_LIST).enqueue(todo(1));
+        store.save(&path).unwrap();
+        store.list_mut(DEFAULT_LIST).enqueue(todo(2));
+        store.save(&path).unwrap();
+        assert_eq!(ids(&Store::load(&path).unwrap()), [1, 2]);
+        assert_eq!(ids(&Store::load(&backup_path(&path)).unwrap()), [1]);
+
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert!(Store::load(&path).unwrap_err().is_corruption());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn move_task_renumbers_only_on_conflict() {
+        let mut store = Store::new();
+        store.list_mut(DEFAULT_LIST).enqueue(todo(1));
+        store.list_mut(DEFAULT_LIST).enqueue(todo(5));
+        let work = store.list_mut("work");
+        work.enqueue(todo(1));
+        work.next_id = 2;
+
+        let moved = |store: &mut Store, id| {
+            store
+                .move_task(id, DEFAULT_LIST, "work")
+                .unwrap()
+                .map(|t| t.id)
+        };
+        assert_eq!(moved(&mut store, 5), Some(5));
+        assert_eq!(moved(&mut store, 1), Some(6));
+        assert_eq!(moved(&mut store, 1), None);
+        let work = store.list("work").unwrap();
+        assert_eq!(work.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 5, 6]);
+        assert_eq!(work.next_id, 7);
+    }
+
+    #[test]
+    fn insert_renumbers_ids_that_would_exhaust_next_id() {
+        let mut store = Store::new();
+        assert_eq!(
+            store.insert("work", todo(u64::MAX - 2)).unwrap().id,
+            u64::MAX - 2
+        );
+        assert_eq!(
+            store.insert(DEFAULT_LIST, todo(u64::MAX - 1)).unwrap().id,
+            1
+        );
+        // Adding a task after the import still has an id to hand out.
+        let queue = store.list_mut(DEFAULT_LIST);
+        assert_eq!(queue.allocate_id().unwrap(), 2);
+        assert_eq!(queue.next_id, 3);
+
+        let work = store.list_mut("work");
+        assert_eq!(work.allocate_id().unwrap(), u64::MAX - 1);
+        assert!(work.allocate_id().is_err());
+        assert!(store.insert("work", todo(u64::MAX - 2)).is_err());
+    }
+
+    #[test]
+    fn load_migrates_the_headerless_queue() {
+        let dir = std::env::temp_dir().join(format!("persistent-todo-v0-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("todo.bin");
+        // Original layout: inbox, outbox, next_id, with `Todo { id, description, created_at }`.
+        let mut bytes = borsh::to_vec(&vec![(7u64, "legacy".to_string(), 42u64)]).unwrap();
+        bytes.extend_from_slice(&borsh::to_vec(&Vec::<u8>::new()).unwrap());
+        bytes.extend_from_slice(&8u64.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+
+        let store = Store::load(&path).unwrap();
+        let queue = store.list(DEFAULT_LIST).unwrap();
+        let todo = queue.iter().next().unwrap();
+        assert_eq!((todo.id, todo.description.as_str()), (7, "legacy"));
+        assert_eq!(queue.next_id, 8);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,8 +1,142 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+
 use borsh::{BorshDeserialize, BorshSerialize};
 
-#[derive(Debug, BorshSerialize, BorshDeserialize)]
+use crate::date::Date;
+use crate::recurrence::Recurrence;
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct Todo {
     pub id: u64,
     pub description: String,
     pub created_at: u64,
+    pub priority: Option<Priority>,
+    pub due: Option<Date>,
+    pub tags: Vec<String>,
+    /// When set, completing the task enqueues its next occurrence.
+    pub recurrence: Option<Recurrence>,
+}
+
+/// `Todo` as saved before priorities and due dates.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct TodoV0 {
+    id: u64,
+    description: String,
+    created_at: u64,
+}
+
+impl From<TodoV0> for Todo {
+    fn from(todo: TodoV0) -> Self {
+        Todo {
+            id: todo.id,
+            description: todo.description,
+            created_at: todo.created_at,
+            priority: None,
+            due: None,
+            tags: Vec::new(),
+            recurrence: None,
+        }
+    }
+}
+
+impl Todo {
+    /// Sort key for `next`: higher priority first (unset counts as medium), then tasks with a
+    /// due date, earliest first. Equal keys keep FIFO order.
+    pub fn urgency(&self) -> impl Ord + use<> {
+        (
+            self.priority.unwrap_or_default(),
+            self.due.is_some(),
+            Reverse(self.due),
+        )
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Adds `tag` unless the task already has it.
+    pub fn tag(&mut self, tag: String) {
+        if !self.has_tag(&tag) {
+            self.tags.push(tag);
+        }
+    }
+}
+
+impl fmt::Display for Todo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.id, self.description)?;
+        let mut details = Vec::new();
+        if let Some(priority) = self.priority {
+            details.push(priority.to_string());
+        }
+        if let Some(due) = self.due {
+            details.push(format!("due {due}"));
+        }
+        if let Some(recurrence) = &self.recurrence {
+            details.push(format!("repeats {recurrence}"));
+        }
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+        for tag in &self.tags {
+            write!(f, " #{tag}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A finished task kept in the archive.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct Completed {
+    pub todo: Todo,
+    /// The list the task was completed from.
+    pub list: String,
+    /// Unix timestamp, in seconds.
+    pub completed_at: u64,
+    /// Id of the next occurrence enqueued for a recurring task, withdrawn again by `undo`.
+    pub spawned: Option<u64>,
+}
+
+impl fmt::Display for Completed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let day = Date::from_unix(self.completed_at);
+        write!(f, "{} [completed {day}]", self.todo)
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, BorshSerialize, BorshDeserialize,
+)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "medium" | "med" => Ok(Priority::Medium),
+            "high" => Ok(Priority::High),
+            _ => Err(format!(
+                "invalid priority '{s}', expected low, medium or high"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        })
+    }
 }